@@ -0,0 +1,103 @@
+//! The stable codes of diagnostics, e.g. `R0001`.
+//!
+//! A code identifies a kind of problem, and never changes once it's been
+//! given out, even if the wording of the diagnostics using it does.  Codes
+//! are never reused either: if a kind of problem goes away, so does its
+//! code, for good.
+//!
+//! The codes are grouped by the stage of compilation that reports them:
+//!
+//! - `R00xx`: lexing
+//! - `R01xx`: parsing
+//! - `R02xx`: name resolution and other semantic checks
+//!
+//! Each code has a long-form explanation with examples, in the
+//! "explanations" directory next to this file, which `rune --explain`
+//! prints.
+
+/// A diagnostic code, along with its title and explanation.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub explanation: &'static str,
+}
+
+macro_rules! error_codes {
+    ($($name:ident = $code:expr, $title:expr;)*) => {
+        $(
+            pub const $name: ErrorCode = ErrorCode {
+                code: $code,
+                title: $title,
+                explanation: include_str!(concat!("explanations/", $code, ".md")),
+            };
+        )*
+
+        /// All of the codes, in order.
+        pub const ALL: &'static [ErrorCode] = &[$($name),*];
+    }
+}
+
+error_codes! {
+    // Lexing
+    UNTERMINATED_STRING = "R0001", "Unterminated string literal";
+    UNTERMINATED_RAW_STRING = "R0002", "Unterminated raw string literal";
+    UNTERMINATED_CODEPOINT = "R0003", "Unterminated codepoint literal";
+    UNEXPECTED_CHARACTER = "R0004", "Unexpected character";
+    MISSING_DIGITS = "R0005", "Missing digits after number base prefix";
+    INVALID_DIGIT = "R0006", "Invalid digit for the base of a number literal";
+    INVALID_NUMBER_SUFFIX = "R0007", "Invalid number literal suffix";
+    MISPLACED_DOC_COMMENT = "R0008", "Misplaced doc comment";
+    CONFUSABLE_IDENTIFIER = "R0009", "Identifier with confusable characters";
+
+    // Parsing
+    UNEXPECTED_TOKEN = "R0100", "Unexpected token";
+    NUMBER_OUT_OF_RANGE = "R0101", "Number literal out of range";
+    INVALID_NUMBER = "R0102", "Invalid number literal";
+    INVALID_ESCAPE = "R0103", "Invalid escape sequence";
+    INVALID_CODEPOINT_LITERAL = "R0104", "Codepoint literal without exactly one codepoint";
+    INVALID_IMPORT_PATH = "R0105", "Invalid import path";
+    MISSING_INITIALIZER = "R0106", "Declaration without a required initializer";
+    MISSING_TYPE = "R0107", "Type declaration without a type";
+    AMBIGUOUS_CALL = "R0108", "Ambiguous function call sugar";
+    CALL_WITHOUT_NAME = "R0109", "Function call sugar without a function name";
+}
+
+
+/// Looks up a code, e.g. "R0001".  Lowercase is accepted too.
+pub fn find(code: &str) -> Option<ErrorCode> {
+    ALL.iter().find(|c| c.code.eq_ignore_ascii_case(code)).cloned()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_well_formed() {
+        for (i, c) in ALL.iter().enumerate() {
+            assert_eq!(c.code.len(), 5, "{}", c.code);
+            assert!(c.code.starts_with("R") && c.code[1..].chars().all(|d| d.is_ascii_digit()), "{}", c.code);
+            assert!(ALL[..i].iter().all(|other| other.code != c.code), "Duplicate code {}", c.code);
+        }
+    }
+
+    #[test]
+    fn explanations() {
+        // Every explanation starts with its code and title, and has an
+        // example.
+        for c in ALL {
+            assert!(c.explanation.starts_with(&format!("{}: {}\n", c.code, c.title)), "{}", c.code);
+            assert!(c.explanation.contains("```"), "{}", c.code);
+        }
+    }
+
+    #[test]
+    fn lookup() {
+        assert_eq!(find("R0001"), Some(UNTERMINATED_STRING));
+        assert_eq!(find("r0105"), Some(INVALID_IMPORT_PATH));
+        assert_eq!(find("R9999"), None);
+        assert_eq!(find(""), None);
+    }
+}
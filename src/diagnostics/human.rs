@@ -0,0 +1,451 @@
+//! Rendering diagnostics for humans, in the style of rustc:
+//!
+//! ```text
+//! error: Invalid digit for the base of the number literal.
+//!  --> main.rune:2:9
+//!   |
+//! 2 | val a = 0b102
+//!   |         ^^^^^ only 0 and 1 are binary digits
+//! ```
+//!
+//! Primary labels are underlined with "^" and secondary ones with "-".
+//! The message of the rightmost label on a line goes right after its
+//! underline, and the others hang below it.
+
+use std::cmp::{max, min};
+use unicode_segmentation::UnicodeSegmentation;
+use source_map::{FileId, SourceFile, SourceMap};
+use super::{Diagnostic, Label, Severity};
+
+// How many columns a tab is shown as.
+const TAB_WIDTH: usize = 4;
+
+/// Renders diagnostics as text for humans to read, optionally colored
+/// with ANSI escape codes.
+pub struct HumanRenderer {
+    color: bool,
+}
+
+impl Default for HumanRenderer {
+    fn default() -> HumanRenderer {
+        HumanRenderer::new()
+    }
+}
+
+impl HumanRenderer {
+    /// Creates a renderer without color.
+    pub fn new() -> HumanRenderer {
+        HumanRenderer {
+            color: false,
+        }
+    }
+
+
+    /// Creates a renderer, with or without color.
+    pub fn with_color(color: bool) -> HumanRenderer {
+        HumanRenderer {
+            color: color,
+        }
+    }
+
+
+    /// Renders a diagnostic, looking up the source code its labels point
+    /// at in `source_map`.  The result ends with a newline.
+    pub fn render(&self, diag: &Diagnostic, source_map: &SourceMap) -> String {
+        let level_style = match diag.severity {
+            Severity::Error => Style::Error,
+            Severity::Warning => Style::Warning,
+        };
+        let mut out = String::new();
+        match diag.code {
+            Some(code) => out.push_str(&self.paint(&format!("{}[{}]", diag.severity.name(), code.code), level_style)),
+            None => out.push_str(&self.paint(diag.severity.name(), level_style)),
+        }
+        out.push_str(&self.paint(&format!(": {}", diag.message), Style::Bold));
+        out.push('\n');
+
+        // The gutter is as wide as the largest line number shown
+        let gutter = diag.labels.iter().map(|l| {
+            let file = source_map.file(l.span.file_id);
+            let last_byte = if l.span.hi > l.span.lo { l.span.hi - 1 } else { l.span.lo };
+            (file.line_index(last_byte as usize) + 1).to_string().len()
+        }).max().unwrap_or(0);
+        let pad = " ".repeat(gutter);
+
+        // Show a snippet for each file, starting with the file of the
+        // primary label.
+        let mut files: Vec<FileId> = Vec::new();
+        if let Some(span) = diag.primary_span() {
+            files.push(span.file_id);
+        }
+        for l in &diag.labels {
+            if !files.contains(&l.span.file_id) {
+                files.push(l.span.file_id);
+            }
+        }
+        for (i, &file_id) in files.iter().enumerate() {
+            let file = source_map.file(file_id);
+            let labels: Vec<&Label> = diag.labels.iter().filter(|l| l.span.file_id == file_id).collect();
+            let location = labels.iter().find(|l| l.primary).unwrap_or(&labels[0]).span;
+            let pos = file.line_col(location.lo as usize);
+            out.push_str(&pad);
+            out.push_str(&self.paint(if i == 0 { "-->" } else { ":::" }, Style::Gutter));
+            out.push_str(&format!(" {}:{}:{}\n", file.name(), pos.line + 1, pos.column + 1));
+            out.push_str(&pad);
+            out.push_str(&self.paint(" |", Style::Gutter));
+            out.push('\n');
+            self.render_snippet(&mut out, file, &labels[..], gutter, level_style);
+        }
+
+        // Notes, help, and suggestions
+        let suggestions: Vec<String> = diag.suggestions.iter().map(|s| {
+            format!("{}: `{}`", s.message, s.replacement)
+        }).collect();
+        if !diag.notes.is_empty() || !diag.help.is_empty() || !suggestions.is_empty() {
+            if !diag.labels.is_empty() {
+                out.push_str(&pad);
+                out.push_str(&self.paint(" |", Style::Gutter));
+                out.push('\n');
+            }
+            for (kind, texts) in [("note", &diag.notes), ("help", &diag.help), ("help", &suggestions)].iter() {
+                for text in texts.iter() {
+                    out.push_str(&pad);
+                    out.push_str(&self.paint(" =", Style::Gutter));
+                    out.push_str(&format!(" {}: {}\n", self.paint(kind, Style::Bold), text));
+                }
+            }
+        }
+
+        out
+    }
+
+
+    // Renders the lines of a file that the labels point at, with their
+    // underlines and messages.
+    fn render_snippet(&self, out: &mut String, file: &SourceFile, labels: &[&Label], gutter: usize, level_style: Style) {
+        let mut segments = Vec::new();
+        for label in labels {
+            split_label(file, label, level_style, &mut segments);
+        }
+        let mut lines: Vec<usize> = segments.iter().map(|s| s.line).collect();
+        lines.sort();
+        lines.dedup();
+
+        let pad = " ".repeat(gutter);
+        let mut prev_line: Option<usize> = None;
+        for &line in &lines {
+            // Mark skipped lines
+            if let Some(prev) = prev_line {
+                if line > prev + 1 {
+                    out.push_str(&self.paint("...", Style::Gutter));
+                    out.push('\n');
+                }
+            }
+            prev_line = Some(line);
+
+            // The source line
+            let text = file.line_text(line).replace('\t', &" ".repeat(TAB_WIDTH));
+            out.push_str(&self.paint(&format!("{:>1$} |", line + 1, gutter), Style::Gutter));
+            if !text.is_empty() {
+                out.push(' ');
+                out.push_str(&text);
+            }
+            out.push('\n');
+
+            // Its annotations
+            let mut segs: Vec<&Segment> = segments.iter().filter(|s| s.line == line).collect();
+            segs.sort_by_key(|s| (s.start, !s.primary));
+            self.render_annotations(out, &pad, &segs[..]);
+        }
+    }
+
+
+    // Renders the underlines and messages of the segments on one line.
+    // `segs` must be sorted by starting column.
+    fn render_annotations(&self, out: &mut String, pad: &str, segs: &[&Segment]) {
+        // Underlines, with primary ones drawn over secondary ones
+        let width = segs.iter().map(|s| s.end).max().unwrap_or(0);
+        let mut marks: Vec<Option<usize>> = vec![None; width];
+        let secondary = segs.iter().enumerate().filter(|&(_, s)| !s.primary);
+        let primary = segs.iter().enumerate().filter(|&(_, s)| s.primary);
+        for (i, seg) in secondary.chain(primary) {
+            for mark in &mut marks[seg.start..seg.end] {
+                *mark = Some(i);
+            }
+        }
+        let mut underline = String::new();
+        let mut col = 0;
+        while col < width {
+            match marks[col] {
+                None => {
+                    underline.push(' ');
+                    col += 1;
+                }
+                Some(i) => {
+                    let seg = segs[i];
+                    let run = marks[col..].iter().take_while(|&&m| m == Some(i)).count();
+                    let c = if seg.primary { "^" } else { "-" };
+                    underline.push_str(&self.paint(&c.repeat(run), seg.style));
+                    col += run;
+                }
+            }
+        }
+
+        // The rightmost label's message goes on the same line
+        let last = segs[segs.len() - 1];
+        if !last.message.is_empty() {
+            underline.push(' ');
+            underline.push_str(&self.paint(last.message, last.style));
+        }
+        out.push_str(pad);
+        out.push_str(&self.paint(" |", Style::Gutter));
+        out.push(' ');
+        out.push_str(&underline);
+        out.push('\n');
+
+        // The others hang below, from right to left
+        let mut pending: Vec<&Segment> = segs[..(segs.len() - 1)].iter().cloned().filter(|s| !s.message.is_empty()).collect();
+        while let Some(seg) = pending.pop() {
+            let mut connectors = pending.clone();
+            connectors.push(seg);
+            self.render_hanging_line(out, pad, &connectors[..], None);
+            self.render_hanging_line(out, pad, &pending[..], Some(seg));
+        }
+    }
+
+
+    // Renders a line of "|" connectors below the given segments, followed
+    // by the message of `message_seg` if given.
+    fn render_hanging_line(&self, out: &mut String, pad: &str, connectors: &[&Segment], message_seg: Option<&Segment>) {
+        let mut line = String::new();
+        let mut col = 0;
+        for seg in connectors {
+            if seg.start >= col {
+                line.push_str(&" ".repeat(seg.start - col));
+                line.push_str(&self.paint("|", seg.style));
+                col = seg.start + 1;
+            }
+        }
+        if let Some(seg) = message_seg {
+            line.push_str(&" ".repeat(seg.start.saturating_sub(col)));
+            line.push_str(&self.paint(seg.message, seg.style));
+        }
+        out.push_str(pad);
+        out.push_str(&self.paint(" |", Style::Gutter));
+        out.push(' ');
+        out.push_str(&line);
+        out.push('\n');
+    }
+
+
+    // Wraps text in the ANSI escape codes for a style, if color is on.
+    fn paint(&self, text: &str, style: Style) -> String {
+        if !self.color {
+            return text.to_string();
+        }
+        let code = match style {
+            Style::Error => "\x1b[1;31m",
+            Style::Warning => "\x1b[1;33m",
+            Style::Gutter => "\x1b[1;34m",
+            Style::Bold => "\x1b[1m",
+        };
+        format!("{}{}\x1b[0m", code, text)
+    }
+}
+
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+enum Style {
+    Error,
+    Warning,
+    Gutter,
+    Bold,
+}
+
+
+// The part of a label on a single line, in display columns.  Only the
+// last line of a label shows its message.
+struct Segment<'a> {
+    line: usize,
+    start: usize,
+    end: usize,
+    primary: bool,
+    style: Style,
+    message: &'a str,
+}
+
+
+// Splits a label into a segment for each line it covers.
+fn split_label<'a>(file: &SourceFile, label: &'a Label, level_style: Style, segments: &mut Vec<Segment<'a>>) {
+    let lo = label.span.lo as usize;
+    let hi = label.span.hi as usize;
+    let first_line = file.line_index(lo);
+    let last_line = if hi > lo { file.line_index(hi - 1) } else { first_line };
+    for line in first_line..(last_line + 1) {
+        let line_start = file.line_start(line);
+        let text = file.line_text(line);
+        let from = if line == first_line { min(lo - line_start, text.len()) } else { 0 };
+        let to = if line == last_line { min(hi - line_start, text.len()) } else { text.len() };
+        let start = display_width(&text[..from]);
+        segments.push(Segment {
+            line: line,
+            start: start,
+            end: max(display_width(&text[..max(from, to)]), start + 1),
+            primary: label.primary,
+            style: if label.primary { level_style } else { Style::Gutter },
+            message: if line == last_line { &label.message[..] } else { "" },
+        });
+    }
+}
+
+
+// The number of columns text takes up when shown.
+fn display_width(text: &str) -> usize {
+    text.graphemes(true).map(|g| if g == "\t" { TAB_WIDTH } else { 1 }).sum()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs::File;
+    use std::io::{Read, Write};
+    use std::path::PathBuf;
+    use super::*;
+    use source_map::{SourceMap, Span};
+    use diagnostics::codes;
+    use lexer::lex_str;
+
+    // Checks rendered output against the snapshot file of the given name
+    // in "src/diagnostics/snapshots".  Run the tests with the environment
+    // variable RUNE_UPDATE_SNAPSHOTS set to write the snapshots instead.
+    fn assert_snapshot(name: &str, actual: &str) {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("src/diagnostics/snapshots");
+        path.push(format!("{}.txt", name));
+        if env::var("RUNE_UPDATE_SNAPSHOTS").is_ok() {
+            File::create(&path).unwrap().write_all(actual.as_bytes()).unwrap();
+            return;
+        }
+        let mut expected = String::new();
+        File::open(&path).and_then(|mut f| f.read_to_string(&mut expected))
+            .unwrap_or_else(|_| panic!("Missing snapshot {:?}.  Run with RUNE_UPDATE_SNAPSHOTS=1 to create it.", path));
+        assert!(expected == actual, "Snapshot \"{}\" doesn't match.\n--- Expected:\n{}\n--- Actual:\n{}", name, expected, actual);
+    }
+
+    // A source map with a single file, and a function to get the span of
+    // the nth occurrence of some text in it.
+    fn map_with(text: &str) -> SourceMap {
+        let mut map = SourceMap::new();
+        map.add_file("test.rune", text.to_string());
+        map
+    }
+
+    fn find(map: &SourceMap, needle: &str, nth: usize) -> Span {
+        let text = map.file(FileId(0)).text();
+        let lo = text.match_indices(needle).nth(nth).unwrap().0;
+        Span::new(FileId(0), lo, lo + needle.len())
+    }
+
+    fn render(diag: &Diagnostic, map: &SourceMap) -> String {
+        HumanRenderer::new().render(diag, map)
+    }
+
+    #[test]
+    fn lex_errors() {
+        let text = "val a = 0x\nval b = \"größe\\q\" ; 1";
+        let map = map_with(text);
+        let out: Vec<_> = lex_str(text).1.iter().map(|e| render(&e.to_diagnostic(), &map)).collect();
+        assert_snapshot("lex_errors", &out.join("\n"));
+    }
+
+    #[test]
+    fn parse_error() {
+        let map = map_with("val s = \"a\\qb\"");
+        let diag = Diagnostic::error("Unknown escape sequence \"\\q\".")
+            .with_primary_label(find(&map, "\\q", 0), "unknown escape");
+        assert_snapshot("parse_error", &render(&diag, &map));
+    }
+
+    #[test]
+    fn multiple_labels_one_line() {
+        let map = map_with("val x: Int = foo(1, \"two\")");
+        let diag = Diagnostic::error("Mismatched types.")
+            .with_primary_label(find(&map, "\"two\"", 0), "expected Int, found String")
+            .with_secondary_label(find(&map, "foo", 0), "in this call")
+            .with_secondary_label(find(&map, "Int", 0), "expected because of this");
+        assert_snapshot("multiple_labels_one_line", &render(&diag, &map));
+    }
+
+    #[test]
+    fn multiple_lines() {
+        let map = map_with("fn foo()\n    a\n    b\n    c\n    foo()\n");
+        let diag = Diagnostic::warning("Recursive call.")
+            .with_primary_label(find(&map, "foo", 1), "calls itself")
+            .with_secondary_label(find(&map, "fn foo", 0), "");
+        assert_snapshot("multiple_lines", &render(&diag, &map));
+    }
+
+    #[test]
+    fn multi_line_span() {
+        let map = map_with("val s = \"Suddenly\n  a string");
+        let diag = Diagnostic::error("Unterminated string literal.")
+            .with_primary_label(find(&map, "\"Suddenly\n  a string", 0), "reaches the end of the file");
+        assert_snapshot("multi_line_span", &render(&diag, &map));
+    }
+
+    #[test]
+    fn notes_and_help() {
+        let map = map_with("#: Docs\n1 + 2");
+        let diag = Diagnostic::error("Doc comment is not followed by a documentable declaration.")
+            .with_primary_label(find(&map, "#: Docs", 0), "")
+            .with_note("Doc comments document the declaration right after them.")
+            .with_help("Use a regular comment (\"#\") instead.");
+        assert_snapshot("notes_and_help", &render(&diag, &map));
+    }
+
+    #[test]
+    fn code_and_suggestion() {
+        let map = map_with("val a = 0x");
+        let diag = Diagnostic::error("Missing digits after number base prefix.")
+            .with_code(codes::MISSING_DIGITS)
+            .with_primary_label(find(&map, "0x", 0), "")
+            .with_suggestion(find(&map, "0x", 0), "0x0", "Add a digit");
+        assert_snapshot("code_and_suggestion", &render(&diag, &map));
+    }
+
+    #[test]
+    fn no_labels() {
+        let map = SourceMap::new();
+        let diag = Diagnostic::error("Unexpected end of file.").with_note("Nothing to see here.");
+        assert_eq!(render(&diag, &map), "error: Unexpected end of file.\n = note: Nothing to see here.\n");
+    }
+
+    #[test]
+    fn empty_span_and_tabs() {
+        let map = map_with("\tval a =\t\n");
+        let diag = Diagnostic::error("Expected expression.")
+            .with_primary_label(find(&map, "\n", 0), "here");
+        assert_snapshot("empty_span_and_tabs", &render(&diag, &map));
+    }
+
+    #[test]
+    fn multiple_files() {
+        let mut map = SourceMap::new();
+        map.add_file("main.rune", "%import util\nutil.foo()".to_string());
+        map.add_file("util.rune", "fn bar()".to_string());
+        let diag = Diagnostic::error("No function named \"foo\" in \"util\".")
+            .with_primary_label(Span::new(FileId(0), 18, 21), "not found")
+            .with_secondary_label(Span::new(FileId(1), 3, 6), "did you mean this?");
+        assert_snapshot("multiple_files", &render(&diag, &map));
+    }
+
+    #[test]
+    fn color() {
+        let map = map_with("val a = 0x");
+        let diag = Diagnostic::warning("Careful.")
+            .with_primary_label(find(&map, "0x", 0), "this")
+            .with_secondary_label(find(&map, "a", 1), "that");
+        assert_snapshot("color", &HumanRenderer::with_color(true).render(&diag, &map));
+    }
+}
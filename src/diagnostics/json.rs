@@ -0,0 +1,139 @@
+//! Rendering diagnostics as JSON, for tools.  The format is documented in
+//! "doc/diagnostics.md", and any incompatible change to it must bump
+//! `JSON_VERSION`.
+
+use std::collections::BTreeMap;
+use rustc_serialize::json::Json;
+use source_map::{SourceMap, Span};
+use super::{Diagnostic, HumanRenderer};
+
+/// The version of the JSON diagnostics format.
+pub const JSON_VERSION: u64 = 1;
+
+/// Renders diagnostics as a JSON document.
+pub fn render_json(diags: &[Diagnostic], source_map: &SourceMap) -> String {
+    let doc = object(vec![
+        ("version", Json::U64(JSON_VERSION)),
+        ("diagnostics", Json::Array(diags.iter().map(|d| diagnostic_json(d, source_map)).collect())),
+    ]);
+    format!("{}\n", doc.pretty())
+}
+
+
+fn diagnostic_json(diag: &Diagnostic, source_map: &SourceMap) -> Json {
+    let spans = diag.labels.iter().map(|l| {
+        let mut span = span_json(l.span, source_map);
+        if let Json::Object(ref mut fields) = span {
+            fields.insert("is_primary".to_string(), Json::Boolean(l.primary));
+            fields.insert("label".to_string(), optional_string(&l.message));
+        }
+        span
+    }).collect();
+    let suggestions = diag.suggestions.iter().map(|s| object(vec![
+        ("message", Json::String(s.message.clone())),
+        ("replacement", Json::String(s.replacement.clone())),
+        ("span", span_json(s.span, source_map)),
+    ])).collect();
+
+    object(vec![
+        ("severity", Json::String(diag.severity.name().to_string())),
+        ("code", match diag.code {
+            Some(code) => Json::String(code.code.to_string()),
+            None => Json::Null,
+        }),
+        ("message", Json::String(diag.message.clone())),
+        ("spans", Json::Array(spans)),
+        ("notes", strings(&diag.notes)),
+        ("help", strings(&diag.help)),
+        ("suggestions", Json::Array(suggestions)),
+        ("rendered", Json::String(HumanRenderer::new().render(diag, source_map))),
+    ])
+}
+
+
+// The location of a span.  Lines and columns start at 1, and columns are
+// counted in Unicode scalar values.
+fn span_json(span: Span, source_map: &SourceMap) -> Json {
+    let file = source_map.file(span.file_id);
+    let (lo, hi) = (span.lo as usize, span.hi as usize);
+    object(vec![
+        ("file", Json::String(file.name().to_string())),
+        ("byte_start", Json::U64(lo as u64)),
+        ("byte_end", Json::U64(hi as u64)),
+        ("line_start", Json::U64(file.line_index(lo) as u64 + 1)),
+        ("column_start", Json::U64(file.char_column(lo) as u64 + 1)),
+        ("line_end", Json::U64(file.line_index(hi) as u64 + 1)),
+        ("column_end", Json::U64(file.char_column(hi) as u64 + 1)),
+    ])
+}
+
+
+pub fn object(fields: Vec<(&str, Json)>) -> Json {
+    let mut map = BTreeMap::new();
+    for (name, value) in fields {
+        map.insert(name.to_string(), value);
+    }
+    Json::Object(map)
+}
+
+
+fn strings(texts: &[String]) -> Json {
+    Json::Array(texts.iter().map(|t| Json::String(t.clone())).collect())
+}
+
+
+// An empty string is null.
+fn optional_string(text: &str) -> Json {
+    if text.len() == 0 { Json::Null } else { Json::String(text.to_string()) }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use source_map::FileId;
+    use diagnostics::codes;
+
+    #[test]
+    fn json() {
+        let mut map = SourceMap::new();
+        map.add_file("test.rune", "val a = 1\nval größe = pаypal".to_string());
+        let diags = [
+            Diagnostic::warning("Identifier mixes scripts with confusable characters.")
+                .with_code(codes::CONFUSABLE_IDENTIFIER)
+                .with_primary_label(Span::new(FileId(0), 24, 31), "")
+                .with_secondary_label(Span::new(FileId(0), 14, 21), "another")
+                .with_help("Check the letters.")
+                .with_suggestion(Span::new(FileId(0), 24, 31), "paypal", "Use Latin letters"),
+            Diagnostic::error("Something."),
+        ];
+        let json = Json::from_str(&render_json(&diags[..], &map)).unwrap();
+        assert_eq!(json["version"], Json::U64(1));
+
+        let d = &json["diagnostics"][0];
+        assert_eq!(d["severity"], Json::String("warning".to_string()));
+        assert_eq!(d["code"], Json::String("R0009".to_string()));
+        assert_eq!(d["help"][0], Json::String("Check the letters.".to_string()));
+        assert!(d["rendered"].as_string().unwrap().starts_with("warning[R0009]: Identifier"));
+
+        let span = &d["spans"][0];
+        assert_eq!(span["file"], Json::String("test.rune".to_string()));
+        assert_eq!(span["byte_start"], Json::U64(24));
+        assert_eq!(span["byte_end"], Json::U64(31));
+        assert_eq!(span["line_start"], Json::U64(2));
+        assert_eq!(span["column_start"], Json::U64(13));
+        assert_eq!(span["column_end"], Json::U64(19));
+        assert_eq!(span["is_primary"], Json::Boolean(true));
+        assert_eq!(span["label"], Json::Null);
+        assert_eq!(d["spans"][1]["label"], Json::String("another".to_string()));
+        assert_eq!(d["spans"][1]["column_start"], Json::U64(5));
+
+        let suggestion = &d["suggestions"][0];
+        assert_eq!(suggestion["replacement"], Json::String("paypal".to_string()));
+        assert_eq!(suggestion["span"]["byte_start"], Json::U64(24));
+
+        let d = &json["diagnostics"][1];
+        assert_eq!(d["code"], Json::Null);
+        assert_eq!(d["spans"], Json::Array(vec![]));
+    }
+}
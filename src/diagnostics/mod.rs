@@ -0,0 +1,199 @@
+//! Diagnostics: the errors and warnings reported about source code.
+//!
+//! A `Diagnostic` has a message, and any number of labelled spans that
+//! point at the source code it's about.  One of the labels is usually
+//! the primary one, pointing at the problem itself, while secondary
+//! labels point at related code.  Notes and help text can be added to
+//! give more context or suggest a fix.
+//!
+//! Most diagnostics have a stable code, e.g. `R0001`, which identifies
+//! the kind of problem; the codes and their explanations are in `codes`.
+//!
+//! The lexer and parser errors can be turned into diagnostics, which
+//! are then rendered for humans by `HumanRenderer`, or for tools as JSON
+//! or SARIF (see "doc/diagnostics.md").
+
+#![allow(dead_code)]
+
+pub mod codes;
+mod human;
+mod json;
+mod sarif;
+
+use source_map::{SourceMap, Span};
+
+pub use self::codes::ErrorCode;
+pub use self::human::HumanRenderer;
+pub use self::json::{render_json, JSON_VERSION};
+pub use self::sarif::render_sarif;
+
+/// The formats diagnostics can be rendered in.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+    Sarif,
+}
+
+impl ErrorFormat {
+    /// Looks up a format by its name, as given on the command line.
+    pub fn from_name(name: &str) -> Option<ErrorFormat> {
+        match name {
+            "human" => Some(ErrorFormat::Human),
+            "json" => Some(ErrorFormat::Json),
+            "sarif" => Some(ErrorFormat::Sarif),
+            _ => None,
+        }
+    }
+}
+
+
+/// Renders a batch of diagnostics in the given format.  Human-readable
+/// diagnostics are separated by blank lines, while the machine-readable
+/// formats produce a single document.
+pub fn render(format: ErrorFormat, diags: &[Diagnostic], source_map: &SourceMap, color: bool) -> String {
+    match format {
+        ErrorFormat::Human => {
+            let renderer = HumanRenderer::with_color(color);
+            let rendered: Vec<_> = diags.iter().map(|d| renderer.render(d, source_map)).collect();
+            rendered.join("\n")
+        }
+        ErrorFormat::Json => render_json(diags, source_map),
+        ErrorFormat::Sarif => render_sarif(diags, source_map),
+    }
+}
+
+
+/// How serious a diagnostic is.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    /// The name of the severity, as shown in diagnostics.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+
+/// A span of source code that a diagnostic points at, with an optional
+/// message about it.  An empty message means no message.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+    pub primary: bool,
+}
+
+
+/// A suggested fix: replacing the text of a span.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Suggestion {
+    pub span: Span,
+    pub replacement: String,
+    pub message: String,
+}
+
+
+//=====================================
+/// An error or warning about source code.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: Option<ErrorCode>,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub help: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, message: &str) -> Diagnostic {
+        Diagnostic {
+            severity: severity,
+            code: None,
+            message: message.to_string(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            help: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+
+    pub fn error(message: &str) -> Diagnostic {
+        Diagnostic::new(Severity::Error, message)
+    }
+
+
+    pub fn warning(message: &str) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, message)
+    }
+
+
+    pub fn with_code(mut self, code: ErrorCode) -> Diagnostic {
+        self.code = Some(code);
+        self
+    }
+
+
+    /// Adds a label pointing at the problem itself.
+    pub fn with_primary_label(mut self, span: Span, message: &str) -> Diagnostic {
+        self.labels.push(Label {
+            span: span,
+            message: message.to_string(),
+            primary: true,
+        });
+        self
+    }
+
+
+    /// Adds a label pointing at code related to the problem.
+    pub fn with_secondary_label(mut self, span: Span, message: &str) -> Diagnostic {
+        self.labels.push(Label {
+            span: span,
+            message: message.to_string(),
+            primary: false,
+        });
+        self
+    }
+
+
+    /// Adds a note, giving more context about the problem.
+    pub fn with_note(mut self, note: &str) -> Diagnostic {
+        self.notes.push(note.to_string());
+        self
+    }
+
+
+    /// Adds help text, suggesting how to fix the problem.
+    pub fn with_help(mut self, help: &str) -> Diagnostic {
+        self.help.push(help.to_string());
+        self
+    }
+
+
+    /// Suggests replacing the text of `span` with `replacement` to fix
+    /// the problem.
+    pub fn with_suggestion(mut self, span: Span, replacement: &str, message: &str) -> Diagnostic {
+        self.suggestions.push(Suggestion {
+            span: span,
+            replacement: replacement.to_string(),
+            message: message.to_string(),
+        });
+        self
+    }
+
+
+    /// The span of the first primary label, if there is one.
+    pub fn primary_span(&self) -> Option<Span> {
+        self.labels.iter().find(|l| l.primary).map(|l| l.span)
+    }
+}
@@ -0,0 +1,168 @@
+//! Rendering diagnostics as SARIF 2.1.0, the Static Analysis Results
+//! Interchange Format, which code hosts and CI systems understand.  See
+//! "doc/diagnostics.md" for how diagnostics map onto it.
+
+use rustc_serialize::json::Json;
+use source_map::{SourceMap, Span};
+use super::{Diagnostic, ErrorCode, Severity};
+use super::json::object;
+
+const SARIF_SCHEMA: &'static str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &'static str = "2.1.0";
+
+/// Renders diagnostics as a SARIF log with a single run.
+pub fn render_sarif(diags: &[Diagnostic], source_map: &SourceMap) -> String {
+    // Each distinct code is a rule
+    let mut codes: Vec<ErrorCode> = diags.iter().filter_map(|d| d.code).collect();
+    codes.sort_by_key(|c| c.code);
+    codes.dedup();
+    let rules = codes.iter().map(|c| object(vec![
+        ("id", Json::String(c.code.to_string())),
+        ("shortDescription", message(c.title)),
+    ])).collect();
+
+    let driver = object(vec![
+        ("name", Json::String("rune".to_string())),
+        ("version", Json::String(env!("CARGO_PKG_VERSION").to_string())),
+        ("rules", Json::Array(rules)),
+    ]);
+    let run = object(vec![
+        ("tool", object(vec![("driver", driver)])),
+        ("columnKind", Json::String("unicodeCodePoints".to_string())),
+        ("results", Json::Array(diags.iter().map(|d| result(d, source_map)).collect())),
+    ]);
+    let log = object(vec![
+        ("$schema", Json::String(SARIF_SCHEMA.to_string())),
+        ("version", Json::String(SARIF_VERSION.to_string())),
+        ("runs", Json::Array(vec![run])),
+    ]);
+    format!("{}\n", log.pretty())
+}
+
+
+// A SARIF result for a diagnostic.  Primary labels are its locations,
+// and secondary labels its related locations.  Notes and help are
+// appended to the message, since SARIF has nowhere else for them.
+fn result(diag: &Diagnostic, source_map: &SourceMap) -> Json {
+    let mut text = diag.message.clone();
+    for note in &diag.notes {
+        text.push_str(&format!("\nnote: {}", note));
+    }
+    for help in &diag.help {
+        text.push_str(&format!("\nhelp: {}", help));
+    }
+
+    let locations = diag.labels.iter().filter(|l| l.primary).map(|l| location(l.span, &l.message, source_map)).collect();
+    let related = diag.labels.iter().filter(|l| !l.primary).map(|l| location(l.span, &l.message, source_map)).collect();
+    let fixes = diag.suggestions.iter().map(|s| object(vec![
+        ("description", message(&s.message)),
+        ("artifactChanges", Json::Array(vec![object(vec![
+            ("artifactLocation", artifact_location(s.span, source_map)),
+            ("replacements", Json::Array(vec![object(vec![
+                ("deletedRegion", region(s.span, source_map)),
+                ("insertedContent", object(vec![("text", Json::String(s.replacement.clone()))])),
+            ])])),
+        ])])),
+    ])).collect();
+
+    let mut fields = vec![
+        ("level", Json::String(match diag.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }.to_string())),
+        ("message", message(&text)),
+        ("locations", Json::Array(locations)),
+        ("relatedLocations", Json::Array(related)),
+        ("fixes", Json::Array(fixes)),
+    ];
+    if let Some(code) = diag.code {
+        fields.push(("ruleId", Json::String(code.code.to_string())));
+    }
+    object(fields)
+}
+
+
+fn location(span: Span, label: &str, source_map: &SourceMap) -> Json {
+    let mut fields = vec![
+        ("physicalLocation", object(vec![
+            ("artifactLocation", artifact_location(span, source_map)),
+            ("region", region(span, source_map)),
+        ])),
+    ];
+    if label.len() > 0 {
+        fields.push(("message", message(label)));
+    }
+    object(fields)
+}
+
+
+fn artifact_location(span: Span, source_map: &SourceMap) -> Json {
+    object(vec![("uri", Json::String(source_map.file_name(span).to_string()))])
+}
+
+
+fn region(span: Span, source_map: &SourceMap) -> Json {
+    let file = source_map.file(span.file_id);
+    let (lo, hi) = (span.lo as usize, span.hi as usize);
+    object(vec![
+        ("startLine", Json::U64(file.line_index(lo) as u64 + 1)),
+        ("startColumn", Json::U64(file.char_column(lo) as u64 + 1)),
+        ("endLine", Json::U64(file.line_index(hi) as u64 + 1)),
+        ("endColumn", Json::U64(file.char_column(hi) as u64 + 1)),
+        ("byteOffset", Json::U64(lo as u64)),
+        ("byteLength", Json::U64((hi - lo) as u64)),
+    ])
+}
+
+
+fn message(text: &str) -> Json {
+    object(vec![("text", Json::String(text.to_string()))])
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use source_map::FileId;
+    use diagnostics::codes;
+
+    #[test]
+    fn sarif() {
+        let mut map = SourceMap::new();
+        map.add_file("src/test.rune", "val a = 0x\nval b = 0x".to_string());
+        let diags = [
+            Diagnostic::error("Missing digits after number base prefix.")
+                .with_code(codes::MISSING_DIGITS)
+                .with_primary_label(Span::new(FileId(0), 19, 21), "")
+                .with_secondary_label(Span::new(FileId(0), 8, 10), "also here")
+                .with_note("A note.")
+                .with_suggestion(Span::new(FileId(0), 19, 21), "0x0", "Add a digit"),
+            Diagnostic::warning("Uncoded."),
+        ];
+        let log = Json::from_str(&render_sarif(&diags[..], &map)).unwrap();
+        assert_eq!(log["version"], Json::String("2.1.0".to_string()));
+
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], Json::String("rune".to_string()));
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], Json::String("R0005".to_string()));
+        assert_eq!(run["tool"]["driver"]["rules"][0]["shortDescription"]["text"], Json::String("Missing digits after number base prefix".to_string()));
+
+        let result = &run["results"][0];
+        assert_eq!(result["ruleId"], Json::String("R0005".to_string()));
+        assert_eq!(result["level"], Json::String("error".to_string()));
+        assert_eq!(result["message"]["text"], Json::String("Missing digits after number base prefix.\nnote: A note.".to_string()));
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], Json::String("src/test.rune".to_string()));
+        assert_eq!(location["region"]["startLine"], Json::U64(2));
+        assert_eq!(location["region"]["startColumn"], Json::U64(9));
+        assert_eq!(location["region"]["endColumn"], Json::U64(11));
+        assert_eq!(location["region"]["byteLength"], Json::U64(2));
+        assert_eq!(result["relatedLocations"][0]["message"]["text"], Json::String("also here".to_string()));
+        let replacement = &result["fixes"][0]["artifactChanges"][0]["replacements"][0];
+        assert_eq!(replacement["insertedContent"]["text"], Json::String("0x0".to_string()));
+
+        let result = &run["results"][1];
+        assert_eq!(result["level"], Json::String("warning".to_string()));
+        assert!(result.find("ruleId").is_none());
+    }
+}
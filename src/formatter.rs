@@ -0,0 +1,120 @@
+//! Source code formatting, for `rune fmt`.
+//!
+//! The formatter works on the lossless token stream, so it can only make
+//! changes that don't need a parse tree.  For now it:
+//!
+//! - Removes whitespace at the ends of lines, including in comments.
+//! - Converts "\r\n" line endings to "\n".
+//! - Ends the file with exactly one newline, unless it's empty.
+//!
+//! Everything else, including the contents of literals, is left as is.
+
+use lexer::lex_str_lossless;
+use lexer::lossless::{LosslessToken, Trivia, TriviaKind};
+use lexer::LexError;
+use token::TokenKind;
+use diagnostics::Severity;
+
+/// Formats source code.  Code with lex errors isn't formatted, since its
+/// tokens can't be trusted, and the errors are returned instead.  Lex
+/// warnings don't stop formatting.
+pub fn format_source(text: &str) -> Result<String, Vec<LexError>> {
+    let (tokens, errors) = lex_str_lossless(text);
+    if errors.iter().any(|e| e.kind.severity() == Severity::Error) {
+        return Err(errors);
+    }
+
+    let mut out = String::new();
+    for (i, t) in tokens.iter().enumerate() {
+        let ends_line = is_line_end(t);
+        let next_ends_line = tokens.get(i + 1).is_none_or(is_line_end);
+
+        write_trivia(&t.leading, ends_line, &mut out);
+        match t.token.kind {
+            TokenKind::NewLine => out.push('\n'),
+            TokenKind::DocComment => out.push_str(t.token.text().trim_end()),
+            _ => out.push_str(t.token.text()),
+        }
+        write_trivia(&t.trailing, next_ends_line, &mut out);
+    }
+
+    // Exactly one newline at the end
+    let len = out.trim_end_matches('\n').len();
+    out.truncate(len);
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+
+// Whether the token ends a line, so that whitespace before it is at the
+// end of a line.
+fn is_line_end(t: &LosslessToken) -> bool {
+    t.token.kind == TokenKind::NewLine || t.token.kind == TokenKind::EOF
+}
+
+
+// Writes trivia, trimming the end of comments, and dropping whitespace at
+// the end if it's at the end of a line.
+fn write_trivia(trivia: &[Trivia], at_line_end: bool, out: &mut String) {
+    for (i, t) in trivia.iter().enumerate() {
+        match t.kind {
+            TriviaKind::Comment => out.push_str(t.text.trim_end()),
+            TriviaKind::Whitespace => {
+                if !(at_line_end && i + 1 == trivia.len()) {
+                    out.push_str(t.text);
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(text: &str) -> String {
+        format_source(text).unwrap()
+    }
+
+    #[test]
+    fn trailing_whitespace() {
+        assert_eq!(format("val a = 1  \n\tvar b\t\n"), "val a = 1\n\tvar b\n");
+        assert_eq!(format("val a = 1  # One  \n  \n  # Two \n"), "val a = 1  # One\n\n  # Two\n");
+        assert_eq!(format("#: Doc  \nfn foo\n"), "#: Doc\nfn foo\n");
+    }
+
+    #[test]
+    fn line_endings() {
+        assert_eq!(format("val a\r\nval b\r\n"), "val a\nval b\n");
+        assert_eq!(format("val a"), "val a\n");
+        assert_eq!(format("val a\n\n\n"), "val a\n");
+        assert_eq!(format(""), "");
+        assert_eq!(format(" \n \n"), "");
+    }
+
+    #[test]
+    fn literals_untouched() {
+        assert_eq!(format("val s = '\"a  \r\nb\"'\n"), "val s = '\"a  \r\nb\"'\n");
+    }
+
+    #[test]
+    fn idempotent() {
+        let text = "  val a = 1  # One  \r\n\n\n  \tvar b \n\n";
+        let once = format(text);
+        assert_eq!(format(&once), once);
+    }
+
+    #[test]
+    fn lex_errors() {
+        assert!(format_source("val s = \"abc\n").is_err());
+    }
+
+    #[test]
+    fn lex_warnings() {
+        // A confusable identifier, with a Cyrillic "а"
+        assert_eq!(format("val p\u{430}ypal = 1   \n"), "val p\u{430}ypal = 1\n");
+    }
+}
@@ -0,0 +1,183 @@
+//! Doc comment blocks.
+//!
+//! The lexer emits one `DocComment` token per `#:` line.  Doc comments
+//! with nothing but whitespace between them make up a single block,
+//! which `merge_doc_comments()` turns into a single token whose source
+//! span covers the whole block:
+//!
+//! ```text
+//! #: Adds two numbers
+//! #: together.
+//! fn add ...
+//! ```
+//!
+//! Doc comments are only legal immediately before something that can be
+//! documented, so a block that isn't followed by a declaration is
+//! reported as an error.
+
+use std::collections::VecDeque;
+use token::{Token, TokenKind};
+use super::{LexError, LexErrorKind};
+
+/// Merges consecutive doc comment tokens into doc comment blocks, and
+/// checks that each block is followed by a documentable declaration.
+///
+/// `text` is the full source text that the tokens were lexed from.  The
+/// newline tokens between the doc comments of a block are removed along
+/// with them.  Errors for misplaced blocks are added to `errors`.
+pub fn merge_doc_comments<'a>(text: &'a str, tokens: VecDeque<Token<'a>>, errors: &mut Vec<LexError>) -> VecDeque<Token<'a>> {
+    let mut merged = VecDeque::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let mut block = if tokens[i].kind == TokenKind::DocComment {
+            tokens[i].span
+        }
+        else {
+            merged.push_back(tokens[i]);
+            i += 1;
+            continue;
+        };
+        i += 1;
+
+        // Pull in any following doc comments with only newlines between
+        // them.  Regular comments are dropped by the lexer, so the source
+        // text is checked to make sure they didn't separate the two.
+        let mut j = i;
+        while j < tokens.len() && tokens[j].kind == TokenKind::NewLine {
+            j += 1;
+        }
+        while j < tokens.len() && tokens[j].kind == TokenKind::DocComment {
+            let ss = tokens[j].span;
+            let between = &text[(block.hi as usize)..(ss.lo as usize)];
+            if between.trim().len() > 0 {
+                break;
+            }
+            block = block.merge(ss);
+            i = j + 1;
+            j = i;
+            while j < tokens.len() && tokens[j].kind == TokenKind::NewLine {
+                j += 1;
+            }
+        }
+
+        // Make sure there's something to document
+        if !tokens.get(j).is_some_and(|t| is_documentable(t.kind)) {
+            errors.push(LexError {
+                kind: LexErrorKind::MisplacedDocComment,
+                source: block,
+            });
+        }
+
+        merged.push_back(Token::new(TokenKind::DocComment, &text[block.range()], block));
+    }
+
+    merged
+}
+
+
+/// Returns the text of a doc comment block, with the `#:` prefix of
+/// each line stripped.
+///
+/// A single space after the `#:` is considered part of the prefix, so
+/// that "#: Foo" gives "Foo".  Lines are joined with "\n".
+pub fn doc_comment_text(block: &str) -> String {
+    let mut text = String::with_capacity(block.len());
+    for (i, line) in block.lines().enumerate() {
+        if i > 0 {
+            text.push('\n');
+        }
+        let line = line.trim();
+        let line = line.strip_prefix("#:").unwrap_or(line);
+        let line = line.strip_prefix(' ').unwrap_or(line);
+        text.push_str(line);
+    }
+    text
+}
+
+
+// Whether a kind of token can start a declaration that can be documented.
+fn is_documentable(kind: TokenKind) -> bool {
+    matches!(kind,
+        TokenKind::KEY_Namespace |
+        TokenKind::KEY_Pub |
+        TokenKind::KEY_Unsafe |
+        TokenKind::KEY_Const |
+        TokenKind::KEY_Val |
+        TokenKind::KEY_Var |
+        TokenKind::KEY_Fn |
+        TokenKind::KEY_Struct |
+        TokenKind::KEY_Enum |
+        TokenKind::KEY_Union |
+        TokenKind::KEY_Trait |
+        TokenKind::KEY_Alias |
+        TokenKind::KEY_Type
+    )
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::{lex_str, LexErrorKind};
+    use token::TokenKind;
+
+    fn doc_blocks(text: &str) -> Vec<String> {
+        lex_str(text).0.iter().filter_map(|t| {
+            if t.kind == TokenKind::DocComment { Some(t.text().to_string()) } else { None }
+        }).collect()
+    }
+
+    #[test]
+    fn merge_consecutive() {
+        let text = "#: Hello\n  #: there\n\n#: you\nfn foo";
+        let (tokens, errors) = lex_str(text);
+        assert_eq!(errors.len(), 0);
+        assert_eq!(tokens.len(), 5);
+        assert_eq!(tokens[0].kind, TokenKind::DocComment);
+        assert_eq!(tokens[0].text, "#: Hello\n  #: there\n\n#: you");
+        assert_eq!(tokens[0].span.lo, 0);
+        assert_eq!(tokens[0].span.hi, 27);
+        assert_eq!(doc_comment_text(tokens[0].text), "Hello\nthere\n\nyou");
+        assert_eq!(tokens[1].kind, TokenKind::NewLine);
+        assert_eq!(tokens[2].kind, TokenKind::KEY_Fn);
+    }
+
+    #[test]
+    fn separated_by_comment() {
+        let text = "#: One\n# Not a doc comment\n#: Two\nval a";
+        assert_eq!(doc_blocks(text), vec!["#: One", "#: Two"]);
+    }
+
+    #[test]
+    fn separated_by_code() {
+        let text = "#: One\nval a #: Two\nval b";
+        assert_eq!(doc_blocks(text), vec!["#: One", "#: Two"]);
+    }
+
+    #[test]
+    fn strip_prefix() {
+        let text = "#:No space\n#:  Two spaces\n#:\r\nval a";
+        let (tokens, _) = lex_str(text);
+        assert_eq!(tokens[0].kind, TokenKind::DocComment);
+        assert_eq!(doc_comment_text(tokens[0].text), "No space\n Two spaces\n");
+    }
+
+    #[test]
+    fn not_documentable() {
+        let text = "#: Hello\n#: there\n1 + 2\n#: At the end";
+        let (_, errors) = lex_str(text);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].kind, LexErrorKind::MisplacedDocComment);
+        assert_eq!(&text[errors[0].source.range()], "#: Hello\n#: there");
+        assert_eq!(errors[1].kind, LexErrorKind::MisplacedDocComment);
+        assert_eq!(&text[errors[1].source.range()], "#: At the end");
+    }
+
+    #[test]
+    fn documentable() {
+        for decl in &["namespace", "pub fn", "const", "val", "var", "fn", "struct", "type"] {
+            let text = format!("#: Doc\n{} foo", decl);
+            assert_eq!(lex_str(&text[..]).1.len(), 0, "{}", decl);
+        }
+    }
+}
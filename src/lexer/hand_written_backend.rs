@@ -0,0 +1,261 @@
+//! The hand-written lexer backend.
+//!
+//! The first character of the remaining text determines what kind of
+//! lexeme we might be looking at, and from there we just walk forward
+//! over the characters that can continue it.  All of the lexemes except
+//! string literals and identifiers are made of ASCII characters, so we
+//! can mostly work directly on bytes.
+
+use super::number;
+use super::{Lexeme, scan_string_literal, scan_raw_string_literal, scan_codepoint_literal, scan_identifier, is_identifier_start};
+
+
+/// Finds the lexeme at the start of `text`, which must not be empty.
+/// Returns its kind and its length in bytes.
+pub fn next_lexeme(text: &str) -> (Lexeme, usize) {
+    let bytes = text.as_bytes();
+    
+    match bytes[0] {
+        // Newline
+        b'\r' => {
+            if bytes.get(1) == Some(&b'\n') {
+                return (Lexeme::NewLine, 2);
+            }
+            return (Lexeme::NewLine, 1);
+        }
+        b'\n' => return (Lexeme::NewLine, 1),
+        
+        // Comment or doc comment
+        b'#' => {
+            let n = run_length(bytes, 1, |b| b != b'\r' && b != b'\n');
+            if bytes.get(1) == Some(&b':') {
+                return (Lexeme::DocComment, n);
+            }
+            return (Lexeme::Comment, n);
+        }
+        
+        // White space
+        b' ' | b'\t' => {
+            return (Lexeme::Whitespace, run_length(bytes, 1, |b| b == b' ' || b == b'\t'));
+        }
+        
+        // Punctuation
+        b'(' | b')' | b'[' | b']' | b'{' | b'}' | b',' | b':' | b'@' | b'.' | b'`' | b'$' => {
+            return (Lexeme::Punctuation, 1);
+        }
+        
+        // Operators
+        b if is_operator_byte(b) => {
+            return (Lexeme::Operator, run_length(bytes, 1, is_operator_byte));
+        }
+        
+        // Integer or real number literal
+        b'0'..=b'9' => {
+            return number::scan_number(text);
+        }
+        
+        // String literal
+        b'"' => {
+            let (n, terminated) = scan_string_literal(text);
+            return (Lexeme::String { terminated: terminated }, n);
+        }
+        
+        // Raw string literal or codepoint literal
+        b'\'' => {
+            let ticks = run_length(bytes, 1, |b| b == b'\'');
+            if bytes.get(ticks) == Some(&b'"') {
+                let (n, terminated) = scan_raw_string_literal(text);
+                return (Lexeme::RawString { terminated: terminated }, n);
+            }
+            let (n, terminated) = scan_codepoint_literal(text);
+            return (Lexeme::Codepoint { terminated: terminated }, n);
+        }
+        
+        // Identifier or keyword, ASCII start
+        b'a'..=b'z' | b'A'..=b'Z' => {
+            return (Lexeme::IdentOrKeyword, continue_identifier(text, 1));
+        }
+        
+        // Identifier of a generic parameter
+        b'_' if text[1..].chars().next().is_some_and(is_identifier_start) => {
+            return (Lexeme::IdentGeneric, scan_identifier(text, 1));
+        }
+        
+        // Everything else is either a non-ASCII identifier or unknown
+        _ => {
+            let c = text.chars().next().unwrap();
+            if is_identifier_start(c) {
+                return (Lexeme::IdentOrKeyword, scan_identifier(text, 0));
+            }
+            
+            // Unknown input text, consume a single character
+            return (Lexeme::Unknown, c.len_utf8());
+        }
+    }
+}
+
+
+// Returns the end of the identifier that continues from byte index
+// `start`, staying on the fast path for as long as the identifier is
+// ASCII.
+fn continue_identifier(text: &str, start: usize) -> usize {
+    let bytes = text.as_bytes();
+    let n = run_length(bytes, start, is_ident_byte);
+    if n < bytes.len() && bytes[n] >= 0x80 {
+        return scan_identifier(text, n);
+    }
+    return n;
+}
+
+
+// Returns the index of the first byte at or after `start` that doesn't
+// satisfy `pred`.
+fn run_length<F: Fn(u8) -> bool>(bytes: &[u8], start: usize, pred: F) -> usize {
+    let mut i = start;
+    while i < bytes.len() && pred(bytes[i]) {
+        i += 1;
+    }
+    return i;
+}
+
+fn is_operator_byte(b: u8) -> bool {
+    matches!(b, b'-' | b'+' | b'/' | b'*' | b'%' | b'|' | b'&' | b'!' | b'~' | b'=' | b'<' | b'>')
+}
+
+fn is_digit(b: u8) -> bool {
+    b.is_ascii_digit()
+}
+
+fn is_alpha(b: u8) -> bool {
+    b.is_ascii_alphabetic()
+}
+
+fn is_ident_byte(b: u8) -> bool {
+    is_alpha(b) || is_digit(b) || b == b'_'
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::Read;
+    use std::time::Instant;
+    use super::super::{lex_str_with_backend, Backend};
+    
+    // Lexes the text with both backends, and checks that they agree.
+    fn assert_backends_agree(text: &str) {
+        let (regex_tokens, regex_errors) = lex_str_with_backend(text, Backend::Regex);
+        let (hand_tokens, hand_errors) = lex_str_with_backend(text, Backend::HandWritten);
+        
+        assert_eq!(regex_tokens, hand_tokens, "Token mismatch for {:?}", text);
+        assert_eq!(regex_errors, hand_errors, "Error mismatch for {:?}", text);
+    }
+    
+    // Reads in all of the example files.
+    fn example_texts() -> Vec<String> {
+        let mut texts = Vec::new();
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/doc/examples");
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "rune") {
+                let mut text = String::new();
+                fs::File::open(&path).unwrap().read_to_string(&mut text).unwrap();
+                texts.push(text);
+            }
+        }
+        assert!(texts.len() > 0);
+        return texts;
+    }
+    
+    // A tiny xorshift random number generator, so that the generated
+    // inputs are the same on every run.
+    struct Rng(u32);
+    
+    impl Rng {
+        fn next(&mut self) -> u32 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 17;
+            self.0 ^= self.0 << 5;
+            return self.0;
+        }
+    }
+    
+    // Generates random text out of bits and pieces of Rune syntax (and
+    // some things that aren't).
+    fn generate_text(rng: &mut Rng, piece_count: usize) -> String {
+        let pieces = [
+            " ", "  ", "\t", "\n", "\r", "\r\n", "#", "#:", "# comment", "#: doc",
+            "(", ")", "[", "]", "{", "}", ",", ":", "@", ".", "`", "$",
+            "-", "+", "/", "*", "%", "|", "&", "!", "~", "=", "<", ">", "->", "==",
+            "0", "42", "1.", ".5", "12.34", "1.2.3", "0x1F", "0o", "0b10", "1_000",
+            "1e-3", "e", "E+", "u8", "f32", "i", "42u8", "1.0f32",
+            "\"", "\"str\"", "\\", "\\\"", "'", "''", "'\"", "\"'", "''\"", "\"''", "'a'", "'\\''", "'\\u{41}'",
+            "a", "foo", "Bar_1", "_", "_T", "__x", "_1",
+            "var", "val", "const", "fn", "type", "namespace", "return",
+            "ö", "速度", "\u{430}", "\u{308}", "😀", "_ö", ";", "?", "^",
+        ];
+        let mut text = String::new();
+        for _ in 0..piece_count {
+            text.push_str(pieces[rng.next() as usize % pieces.len()]);
+        }
+        return text;
+    }
+    
+    #[test]
+    fn backends_agree_on_examples() {
+        for text in example_texts() {
+            assert_backends_agree(&text);
+        }
+    }
+    
+    #[test]
+    fn backends_agree_on_single_characters() {
+        for b in 0u8..128 {
+            let c = b as char;
+            assert_backends_agree(&c.to_string());
+            assert_backends_agree(&format!("a{}b", c));
+        }
+        for c in ['ö', '速', '😀'].iter() {
+            assert_backends_agree(&c.to_string());
+        }
+    }
+    
+    #[test]
+    fn backends_agree_on_generated_text() {
+        let mut rng = Rng(0x1234_5678);
+        for _ in 0..2000 {
+            let piece_count = (rng.next() % 40) as usize;
+            let text = generate_text(&mut rng, piece_count);
+            assert_backends_agree(&text);
+        }
+    }
+    
+    // Reports the lexing speed of both backends.  Run with:
+    //
+    //     cargo test --release -- --ignored --nocapture benchmark_backends
+    #[test]
+    #[ignore]
+    fn benchmark_backends() {
+        let mut text = String::new();
+        for _ in 0..10 {
+            for t in example_texts() {
+                text.push_str(&t);
+            }
+        }
+        let iterations = 20;
+        
+        for &backend in [Backend::Regex, Backend::HandWritten].iter() {
+            let start = Instant::now();
+            let mut token_count = 0;
+            for _ in 0..iterations {
+                token_count += lex_str_with_backend(&text, backend).0.len();
+            }
+            let elapsed = start.elapsed();
+            let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 * 1e-9;
+            println!(
+                "{:?}: {} tokens in {:.3}s, {:.0} tokens/sec",
+                backend, token_count, seconds, token_count as f64 / seconds
+            );
+        }
+    }
+}
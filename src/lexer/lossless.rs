@@ -0,0 +1,218 @@
+//! Lossless lexing, for tools like formatters that need to reproduce the
+//! source text exactly.
+//!
+//! In lossless mode each token carries the whitespace and comments
+//! around it as "trivia".  Trivia between two tokens on the same line
+//! is trailing trivia of the first token, while trivia at the start of
+//! a line (i.e. after a `NewLine` token, or at the start of the file) is
+//! leading trivia of the token that follows it:
+//!
+//! ```text
+//! val a = 1  # One
+//! ```
+//!
+//! Here "val" has the trailing trivia " ", and "1" has the trailing
+//! trivia "  " and "# One".
+//!
+//! Concatenating the leading trivia, token, and trailing trivia of every
+//! token reproduces the source text byte-for-byte.  See `to_source()`.
+
+use source_map::Span;
+use token::{Token, TokenKind};
+use super::{Lexer, LexError};
+
+/// The kinds of trivia.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum TriviaKind {
+    Whitespace,
+    Comment,
+}
+
+
+/// A piece of the source text that isn't part of any token.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct Trivia<'a> {
+    pub kind: TriviaKind,
+    pub text: &'a str,
+    pub span: Span,
+}
+
+
+/// A token along with its surrounding trivia.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct LosslessToken<'a> {
+    pub leading: Vec<Trivia<'a>>,
+    pub token: Token<'a>,
+    pub trailing: Vec<Trivia<'a>>,
+}
+
+impl<'a> LosslessToken<'a> {
+    /// Appends the source text of the token and its trivia to `out`.
+    pub fn write_source(&self, out: &mut String) {
+        for t in &self.leading {
+            out.push_str(t.text);
+        }
+        out.push_str(self.token.text());
+        for t in &self.trailing {
+            out.push_str(t.text);
+        }
+    }
+}
+
+
+/// Reassembles the source text from a lossless token stream.
+pub fn to_source(tokens: &[LosslessToken]) -> String {
+    let mut text = String::new();
+    for t in tokens {
+        t.write_source(&mut text);
+    }
+    text
+}
+
+
+////////////////////////////////////////////////
+/// A lexer that produces tokens with their trivia attached.
+///
+/// Unlike `lex_str()`, doc comments are left as one token per line, since
+/// the newline tokens between them need to be kept.
+pub struct LosslessLexer<'a> {
+    lexer: Lexer<'a>,
+    // The next token and its leading trivia, if already lexed.
+    peeked: Option<(Vec<Trivia<'a>>, Token<'a>)>,
+    done: bool,
+}
+
+impl<'a> LosslessLexer<'a> {
+    pub fn new(lexer: Lexer<'a>) -> LosslessLexer<'a> {
+        LosslessLexer {
+            lexer: lexer,
+            peeked: None,
+            done: false,
+        }
+    }
+
+
+    /// The errors encountered so far.
+    pub fn errors(&self) -> &[LexError] {
+        self.lexer.errors()
+    }
+
+
+    /// Takes the errors encountered so far out of the lexer.
+    pub fn take_errors(&mut self) -> Vec<LexError> {
+        self.lexer.take_errors()
+    }
+
+
+    // Lexes the next token, along with the trivia before it.
+    fn lex(&mut self) -> (Vec<Trivia<'a>>, Token<'a>) {
+        let mut trivia = Vec::new();
+        let token = self.lexer.next_token(Some(&mut trivia));
+        (trivia, token)
+    }
+}
+
+
+impl<'a> Iterator for LosslessLexer<'a> {
+    type Item = LosslessToken<'a>;
+
+    fn next(&mut self) -> Option<LosslessToken<'a>> {
+        if self.done {
+            return None;
+        }
+
+        let (leading, token) = match self.peeked.take() {
+            Some(p) => p,
+            None => self.lex(),
+        };
+
+        // Figure out who the trivia after this token belongs to
+        let trailing = if token.kind == TokenKind::EOF {
+            self.done = true;
+            Vec::new()
+        }
+        else {
+            let (between, next) = self.lex();
+            if token.kind == TokenKind::NewLine {
+                self.peeked = Some((between, next));
+                Vec::new()
+            }
+            else {
+                self.peeked = Some((Vec::new(), next));
+                between
+            }
+        };
+
+        Some(LosslessToken {
+            leading: leading,
+            token: token,
+            trailing: trailing,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::lex_str_lossless;
+    use std::fs;
+    use std::io::Read;
+
+    fn assert_round_trip(text: &str) {
+        let (tokens, _) = lex_str_lossless(text);
+        assert_eq!(to_source(&tokens[..]), text);
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_round_trip("");
+        assert_round_trip("   ");
+        assert_round_trip("# Just a comment");
+        assert_round_trip("val a = 1  # One\n\t\tvar b\n");
+        assert_round_trip("\r\n  \r\n#: Doc\r\n#: comment\r\nfn foo\r\n");
+        assert_round_trip("\"Unterminated \n string");
+        assert_round_trip("val ö = '😀'  ~ \u{0B} \u{A0}? ¬\n");
+    }
+
+    #[test]
+    fn round_trip_examples() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/doc/examples");
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let mut text = String::new();
+            fs::File::open(&path).unwrap().read_to_string(&mut text).unwrap();
+            assert_round_trip(&text[..]);
+        }
+    }
+
+    #[test]
+    fn trivia_placement() {
+        let text = "  val a  # Hi\n\tb";
+        let (tokens, _) = lex_str_lossless(text);
+        assert_eq!(tokens.len(), 5);
+
+        // "val"
+        assert_eq!(tokens[0].leading.len(), 1);
+        assert_eq!(tokens[0].leading[0].text, "  ");
+        assert_eq!(tokens[0].trailing.len(), 1);
+        assert_eq!(tokens[0].trailing[0].text, " ");
+
+        // "a"
+        assert_eq!(tokens[1].leading.len(), 0);
+        assert_eq!(tokens[1].trailing.len(), 2);
+        assert_eq!(tokens[1].trailing[0].kind, TriviaKind::Whitespace);
+        assert_eq!(tokens[1].trailing[1].kind, TriviaKind::Comment);
+        assert_eq!(tokens[1].trailing[1].text, "# Hi");
+
+        // Newline
+        assert_eq!(tokens[2].leading.len(), 0);
+        assert_eq!(tokens[2].trailing.len(), 0);
+
+        // "b"
+        assert_eq!(tokens[3].leading.len(), 1);
+        assert_eq!(tokens[3].leading[0].text, "\t");
+        assert_eq!(tokens[3].leading[0].span.lo, 14);
+        assert_eq!(tokens[4].token.kind, TokenKind::EOF);
+    }
+}
@@ -0,0 +1,1160 @@
+#![allow(dead_code)]
+
+pub mod number;
+pub mod doc_comment;
+pub mod lossless;
+pub mod operator;
+mod regex_backend;
+mod hand_written_backend;
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use unicode_xid::UnicodeXID;
+use unicode_normalization::{UnicodeNormalization, is_nfc_quick, IsNormalized};
+use unicode_security::{MixedScript, skeleton};
+use source_map::{FileId, SourceFile, Span};
+use diagnostics::{codes, Diagnostic, ErrorCode, Severity};
+use token::{Token, TokenKind};
+use symbol::Symbol;
+use self::operator::OperatorTable;
+use self::lossless::{Trivia, TriviaKind, LosslessLexer, LosslessToken};
+
+/// Lexes a string slice into an vector of tokens, along with any
+/// errors encountered along the way.
+///
+/// Lexing never fails outright: problematic input is still turned into
+/// tokens (e.g. `TokenKind::Unknown` tokens for stray characters) so that later
+/// stages can carry on, and the problems are reported in the error list.
+///
+/// Unlike iterating over a `Lexer` directly, consecutive doc comments are
+/// merged into doc comment blocks (see `doc_comment::merge_doc_comments()`).
+pub fn lex_str<'a>(text: &'a str) -> (VecDeque<Token<'a>>, Vec<LexError>) {
+    lex_str_with_backend(text, Backend::HandWritten)
+}
+
+/// Same as `lex_str()`, but with an explicitly chosen backend.
+pub fn lex_str_with_backend<'a>(text: &'a str, backend: Backend) -> (VecDeque<Token<'a>>, Vec<LexError>) {
+    lex(Lexer::with_backend(text, backend))
+}
+
+/// Same as `lex_str()`, but for a file in a `SourceMap`, so that the
+/// tokens' spans refer to that file.
+pub fn lex_file<'a>(file: &'a SourceFile) -> (VecDeque<Token<'a>>, Vec<LexError>) {
+    lex(Lexer::for_file(file))
+}
+
+fn lex<'a>(mut lexer: Lexer<'a>) -> (VecDeque<Token<'a>>, Vec<LexError>) {
+    let tokens = lexer.by_ref().collect();
+    let mut errors = lexer.take_errors();
+    let tokens = doc_comment::merge_doc_comments(lexer.full_text, tokens, &mut errors);
+    (tokens, errors)
+}
+
+
+/// Lexes a string slice into a vector of tokens with their surrounding
+/// whitespace and comments attached, along with any errors encountered
+/// along the way.
+///
+/// This is meant for tools that need to reproduce the source text, such
+/// as formatters.  See the `lossless` module for details.
+pub fn lex_str_lossless<'a>(text: &'a str) -> (Vec<LosslessToken<'a>>, Vec<LexError>) {
+    let mut lexer = LosslessLexer::new(Lexer::new(text));
+    let tokens = lexer.by_ref().collect();
+    (tokens, lexer.take_errors())
+}
+
+
+/// Returns the canonical form of an identifier's text, which is its NFC
+/// normalization.
+///
+/// Two identifiers that look the same but are made up of different
+/// sequences of code points (e.g. a precomposed "ö" vs. "o" followed by a
+/// combining diaeresis) have the same canonical form, so this is what
+/// should be used when comparing identifiers.
+pub fn normalize_identifier<'a>(text: &'a str) -> Cow<'a, str> {
+    match is_nfc_quick(text.chars()) {
+        IsNormalized::Yes => Cow::Borrowed(text),
+        _ => Cow::Owned(text.nfc().collect()),
+    }
+}
+
+
+//=====================================
+/// The kinds of problems the lexer can run into.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum LexErrorKind {
+    /// A string literal that reached the end of the file before its
+    /// closing quote.
+    UnterminatedString,
+
+    /// A raw string literal that reached the end of the file before its
+    /// closing quote and ticks.
+    UnterminatedRawString,
+
+    /// A codepoint literal that reached the end of the line before its
+    /// closing tick.
+    UnterminatedCodepoint,
+
+    /// A character that can't start any token.
+    StrayCharacter,
+
+    /// A number literal with a base prefix but no digits, e.g. `0x`.
+    MissingDigits,
+
+    /// A digit that's invalid for the base of its number literal, e.g.
+    /// `0b102`.
+    InvalidDigit,
+
+    /// A number literal with an unknown suffix, or a suffix that doesn't
+    /// fit the literal, e.g. `42abc` or `1.5u8`.
+    InvalidNumberSuffix,
+
+    /// A doc comment that isn't immediately followed by something that
+    /// can be documented, such as a function or type declaration.
+    MisplacedDocComment,
+
+    /// An identifier that mixes scripts and contains characters that are
+    /// easily confused with characters of another script, e.g. a Latin
+    /// identifier with a Cyrillic "а" in it.  This is only a warning.
+    ConfusableIdentifier,
+}
+
+impl LexErrorKind {
+    pub fn message(&self) -> &'static str {
+        match *self {
+            LexErrorKind::UnterminatedString => "Unterminated string literal.",
+            LexErrorKind::UnterminatedRawString => "Unterminated raw string literal.",
+            LexErrorKind::UnterminatedCodepoint => "Unterminated codepoint literal.",
+            LexErrorKind::StrayCharacter => "Unexpected character.",
+            LexErrorKind::MissingDigits => "Missing digits after number base prefix.",
+            LexErrorKind::InvalidDigit => "Invalid digit for the base of the number literal.",
+            LexErrorKind::InvalidNumberSuffix => "Invalid number literal suffix.",
+            LexErrorKind::MisplacedDocComment => "Doc comment is not followed by a documentable declaration.",
+            LexErrorKind::ConfusableIdentifier => "Identifier mixes scripts with confusable characters.",
+        }
+    }
+    
+    pub fn severity(&self) -> Severity {
+        match *self {
+            LexErrorKind::ConfusableIdentifier => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+    
+    /// The stable code of the kind of error.
+    pub fn code(&self) -> ErrorCode {
+        match *self {
+            LexErrorKind::UnterminatedString => codes::UNTERMINATED_STRING,
+            LexErrorKind::UnterminatedRawString => codes::UNTERMINATED_RAW_STRING,
+            LexErrorKind::UnterminatedCodepoint => codes::UNTERMINATED_CODEPOINT,
+            LexErrorKind::StrayCharacter => codes::UNEXPECTED_CHARACTER,
+            LexErrorKind::MissingDigits => codes::MISSING_DIGITS,
+            LexErrorKind::InvalidDigit => codes::INVALID_DIGIT,
+            LexErrorKind::InvalidNumberSuffix => codes::INVALID_NUMBER_SUFFIX,
+            LexErrorKind::MisplacedDocComment => codes::MISPLACED_DOC_COMMENT,
+            LexErrorKind::ConfusableIdentifier => codes::CONFUSABLE_IDENTIFIER,
+        }
+    }
+    
+    /// Suggests how to fix the problem, for the kinds where that's more
+    /// than just undoing it.
+    pub fn help(&self) -> Option<&'static str> {
+        match *self {
+            LexErrorKind::MisplacedDocComment => Some("Use a regular comment (\"#\") instead."),
+            LexErrorKind::ConfusableIdentifier => Some("Check for letters from another script that look like the ones around them."),
+            _ => None,
+        }
+    }
+}
+
+
+// A lex error.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub source: Span,
+}
+
+impl LexError {
+    /// Turns the error into a diagnostic, for reporting.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let diag = Diagnostic::new(self.kind.severity(), self.kind.message())
+            .with_code(self.kind.code())
+            .with_primary_label(self.source, "");
+        match self.kind.help() {
+            Some(help) => diag.with_help(help),
+            None => diag,
+        }
+    }
+}
+
+
+//=====================================
+/// The available lexer backends.
+///
+/// The backends only differ in how they find the extent of each piece
+/// of the source text, and always produce identical tokens.  The regex
+/// backend is the original implementation, and is kept around as a
+/// reference to test the hand-written one against.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Backend {
+    Regex,
+    HandWritten,
+}
+
+
+// The kinds of lexemes that the backends recognize.  The Lexer turns
+// these into actual tokens.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+enum Lexeme {
+    NewLine,
+    DocComment,
+    Directive,
+    Comment,
+    Whitespace,
+    Punctuation,
+    Operator,
+    Real,
+    Int,
+    String { terminated: bool },
+    RawString { terminated: bool },
+    Codepoint { terminated: bool },
+    IdentOrKeyword,
+    IdentGeneric,
+    Unknown,
+}
+
+
+////////////////////////////////////////////////
+/// A lexer over a piece of source text.
+///
+/// The lexer is an iterator that produces tokens lazily, one at a time,
+/// ending with an EOF token.  Any errors encountered along the way are
+/// collected, and can be retrieved with `errors()` or `take_errors()`.
+///
+/// The lexer can also be started from the middle of a text with
+/// `new_at()`, which allows re-lexing just part of a file.  The current
+/// position can be queried between tokens to know where to resume from
+/// later.
+///
+/// The tokens' spans refer to file 0, unless the lexer is created for a
+/// file of a `SourceMap` with `for_file()`.
+pub struct Lexer<'a> {
+    remaining_text: &'a str,
+    full_text: &'a str,
+    file_id: FileId,
+    current_byte_offset: usize,
+    errors: Vec<LexError>,
+    backend: Backend,
+    operators: Cow<'a, OperatorTable>,
+    at_line_start: bool,
+    done: bool,
+}
+
+impl<'a> Lexer<'a> {
+    /// Creates a lexer that starts at the beginning of `text`.
+    pub fn new(text: &'a str) -> Lexer<'a> {
+        Lexer::with_operators(text, FileId(0), 0, Cow::Owned(OperatorTable::for_source(text)))
+    }
+    
+    
+    /// Creates a lexer for a file of a `SourceMap`.
+    pub fn for_file(file: &'a SourceFile) -> Lexer<'a> {
+        Lexer::new_at(file, 0)
+    }
+    
+    
+    /// Creates a lexer that starts at the beginning of `text`, using the
+    /// given backend.
+    pub fn with_backend(text: &'a str, backend: Backend) -> Lexer<'a> {
+        let mut lexer = Lexer::new(text);
+        lexer.backend = backend;
+        lexer
+    }
+    
+    
+    /// Creates a lexer that starts at `byte_offset` within a file of a
+    /// `SourceMap`.
+    ///
+    /// `byte_offset` must lie on a character boundary, and should be at a
+    /// token boundary to get sensible results.
+    ///
+    /// This scans the whole file for declared operators.  To re-lex parts
+    /// of a file repeatedly, build its `OperatorTable` once and use
+    /// `new_at_with_operators()` instead.
+    pub fn new_at(file: &'a SourceFile, byte_offset: usize) -> Lexer<'a> {
+        let operators = OperatorTable::for_source(file.text());
+        Lexer::with_operators(file.text(), file.id(), byte_offset, Cow::Owned(operators))
+    }
+
+
+    /// Creates a lexer that starts at `byte_offset` within a file, like
+    /// `new_at()`, but with an already built table of the operators that
+    /// the file declares, e.g. from `OperatorTable::for_source()`.
+    pub fn new_at_with_operators(file: &'a SourceFile, byte_offset: usize, operators: &'a OperatorTable) -> Lexer<'a> {
+        Lexer::with_operators(file.text(), file.id(), byte_offset, Cow::Borrowed(operators))
+    }
+
+
+    fn with_operators(text: &'a str, file_id: FileId, byte_offset: usize, operators: Cow<'a, OperatorTable>) -> Lexer<'a> {
+        Lexer {
+            remaining_text: &text[byte_offset..],
+            full_text: text,
+            file_id: file_id,
+            current_byte_offset: byte_offset,
+            errors: Vec::new(),
+            backend: Backend::HandWritten,
+            operators: operators,
+            at_line_start: text[..byte_offset].rsplit(['\n', '\r']).next().unwrap().trim().len() == 0,
+            done: false,
+        }
+    }
+    
+    
+    /// The operators the lexer knows about.
+    pub fn operators(&self) -> &OperatorTable {
+        &self.operators
+    }
+    
+    
+    /// The byte offset in the source text that lexing will continue from.
+    pub fn byte_offset(&self) -> usize {
+        self.current_byte_offset
+    }
+    
+    
+    /// The errors encountered so far.
+    pub fn errors(&self) -> &[LexError] {
+        &self.errors[..]
+    }
+    
+    
+    /// Takes the errors encountered so far out of the lexer.
+    pub fn take_errors(&mut self) -> Vec<LexError> {
+        std::mem::take(&mut self.errors)
+    }
+    
+    
+    // Lexes the next token, skipping over any whitespace and comments.
+    // If `trivia` is given, the skipped whitespace and comments are added
+    // to it.
+    fn next_token(&mut self, mut trivia: Option<&mut Vec<Trivia<'a>>>) -> Token<'a> {
+        loop {
+            // End of file
+            if self.remaining_text.len() == 0 {
+                let offset = self.current_byte_offset;
+                return Token::new(TokenKind::EOF, "", Span::new(self.file_id, offset, offset));
+            }
+            
+            // Find the next lexeme
+            let (mut lexeme, mut n) = match self.backend {
+                Backend::Regex => regex_backend::next_lexeme(self.remaining_text),
+                Backend::HandWritten => hand_written_backend::next_lexeme(self.remaining_text),
+            };
+            
+            // The backends find whole runs of operator characters, which
+            // then need to be split into individual operators.  A "%"
+            // directly followed by a name at the start of a line isn't an
+            // operator at all, but a compiler directive.
+            if lexeme == Lexeme::Operator {
+                if self.at_line_start && self.remaining_text.starts_with("%")
+                && self.remaining_text[1..].chars().next().is_some_and(is_identifier_start) {
+                    lexeme = Lexeme::Directive;
+                    n = scan_identifier(self.remaining_text, 1);
+                }
+                else {
+                    n = self.operators.longest_match(&self.remaining_text[0..n]);
+                }
+            }
+            let text = &self.remaining_text[0..n];
+            let ss = Span::new(self.file_id, self.current_byte_offset, self.current_byte_offset + n);
+            
+            // Turn it into a token
+            let token = match lexeme {
+                Lexeme::Whitespace | Lexeme::Comment => {
+                    if let Some(ref mut trivia) = trivia {
+                        trivia.push(Trivia {
+                            kind: if lexeme == Lexeme::Comment { TriviaKind::Comment } else { TriviaKind::Whitespace },
+                            text: text,
+                            span: ss,
+                        });
+                    }
+                    None
+                }
+                
+                Lexeme::NewLine => Some(Token::new(TokenKind::NewLine, text, ss)),
+                
+                Lexeme::DocComment => Some(Token::new(TokenKind::DocComment, text, ss)),
+                
+                Lexeme::Directive => Some(Token::new(TokenKind::Directive, text, ss)),
+                
+                Lexeme::Punctuation => Some(
+                    match text {
+                        "(" => Token::new(TokenKind::LParen, text, ss),
+                        ")" => Token::new(TokenKind::RParen, text, ss),
+                        "[" => Token::new(TokenKind::LSquare, text, ss),
+                        "]" => Token::new(TokenKind::RSquare, text, ss),
+                        "{" => Token::new(TokenKind::LCurly, text, ss),
+                        "}" => Token::new(TokenKind::RCurly, text, ss),
+                        "," => Token::new(TokenKind::Comma, text, ss),
+                        ":" => Token::new(TokenKind::Colon, text, ss),
+                        "@" => Token::new(TokenKind::At, text, ss),
+                        "." => Token::new(TokenKind::Period, text, ss),
+                        "`" => Token::new(TokenKind::BackTick, text, ss),
+                        "$" => Token::new(TokenKind::Dollar, text, ss),
+                        _ => {
+                            self.errors.push(LexError {
+                                kind: LexErrorKind::StrayCharacter,
+                                source: ss,
+                            });
+                            Token::new(TokenKind::Unknown, text, ss)
+                        }
+                    }
+                ),
+                
+                Lexeme::Operator => Some(Token::new(TokenKind::Operator, text, ss)),
+                
+                Lexeme::Real => {
+                    self.check_number(text, ss);
+                    Some(Token::new(TokenKind::LIT_Real, text, ss))
+                }
+                
+                Lexeme::Int => {
+                    self.check_number(text, ss);
+                    Some(Token::new(TokenKind::LIT_Int, text, ss))
+                }
+                
+                Lexeme::String { terminated } => {
+                    if !terminated {
+                        self.errors.push(LexError {
+                            kind: LexErrorKind::UnterminatedString,
+                            source: ss,
+                        });
+                    }
+                    Some(Token::new(TokenKind::LIT_String, text, ss))
+                }
+                
+                Lexeme::RawString { terminated } => {
+                    if !terminated {
+                        self.errors.push(LexError {
+                            kind: LexErrorKind::UnterminatedRawString,
+                            source: ss,
+                        });
+                    }
+                    Some(Token::new(TokenKind::LIT_RawString, text, ss))
+                }
+                
+                Lexeme::Codepoint { terminated } => {
+                    if !terminated {
+                        self.errors.push(LexError {
+                            kind: LexErrorKind::UnterminatedCodepoint,
+                            source: ss,
+                        });
+                    }
+                    Some(Token::new(TokenKind::LIT_Codepoint, text, ss))
+                }
+                
+                Lexeme::IdentOrKeyword => {
+                    let sym = Symbol::intern(&normalize_identifier(text));
+                    match TokenKind::from_keyword(sym) {
+                        Some(kind) => Some(Token::new(kind, text, ss)),
+                        None => {
+                            self.check_confusable_identifier(text, ss);
+                            Some(Token::new_identifier(TokenKind::Identifier, text, ss, sym))
+                        }
+                    }
+                }
+                
+                Lexeme::IdentGeneric => {
+                    self.check_confusable_identifier(text, ss);
+                    Some(Token::new_identifier(TokenKind::IdentifierGeneric, text, ss, Symbol::intern(&normalize_identifier(text))))
+                }
+                
+                Lexeme::Unknown => {
+                    self.errors.push(LexError {
+                        kind: LexErrorKind::StrayCharacter,
+                        source: ss,
+                    });
+                    Some(Token::new(TokenKind::Unknown, text, ss))
+                }
+            };
+            
+            // Update state
+            match lexeme {
+                Lexeme::NewLine => self.at_line_start = true,
+                Lexeme::Whitespace | Lexeme::Comment => {},
+                _ => self.at_line_start = false,
+            }
+            self.current_byte_offset += n;
+            self.remaining_text = &self.remaining_text[n..];
+            
+            if let Some(t) = token {
+                return t;
+            }
+        }
+    }
+    
+    
+    // Reports any problems with a number literal.
+    fn check_number(&mut self, text: &str, ss: Span) {
+        if let Some(kind) = number::check_number(text) {
+            self.errors.push(LexError {
+                kind: kind,
+                source: ss,
+            });
+        }
+    }
+    
+    
+    // Warns about identifiers that mix scripts in a confusable way.  Using
+    // the definitions of Unicode TR39, that's an identifier that is mixed
+    // script, but whose confusable skeleton is single script.  In other
+    // words, it looks like it's written in one script, but isn't.
+    fn check_confusable_identifier(&mut self, text: &str, ss: Span) {
+        if text.is_ascii() || text.is_single_script() {
+            return;
+        }
+        let skeleton: String = skeleton(text).collect();
+        if (&skeleton[..]).is_single_script() {
+            self.errors.push(LexError {
+                kind: LexErrorKind::ConfusableIdentifier,
+                source: ss,
+            });
+        }
+    }
+}
+
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token<'a>;
+    
+    fn next(&mut self) -> Option<Token<'a>> {
+        if self.done {
+            return None;
+        }
+        
+        let token = self.next_token(None);
+        if token.kind == TokenKind::EOF {
+            self.done = true;
+        }
+        return Some(token);
+    }
+}
+
+
+//=====================================
+// Helpers shared by the backends.
+
+// Finds the extent of the codepoint literal at the start of the text.
+// Returns the length in bytes, and whether the literal was properly
+// closed.  If it wasn't, the literal extends to the end of the line.
+//
+// The contents aren't checked here: anything up to the closing tick is
+// part of the literal, and it's up to the parser to complain if that
+// isn't exactly one (possibly escaped) codepoint.
+fn scan_codepoint_literal(text: &str) -> (usize, bool) {
+    let mut last_was_esc = false;
+    for (b, c) in text.char_indices().skip(1) {
+        if c == '\n' || c == '\r' {
+            return (b, false);
+        }
+        else if last_was_esc {
+            last_was_esc = false;
+        }
+        else if c == '\\' {
+            last_was_esc = true;
+        }
+        else if c == '\'' {
+            return (b + 1, true);
+        }
+    }
+    return (text.len(), false);
+}
+
+
+// Returns the byte index of the end of the identifier characters in the
+// text, starting from byte index `start`.  Identifiers follow Unicode
+// TR31: after the first character, they continue with any XID_Continue
+// characters.
+fn scan_identifier(text: &str, start: usize) -> usize {
+    for (b, c) in text[start..].char_indices() {
+        if !UnicodeXID::is_xid_continue(c) {
+            return start + b;
+        }
+    }
+    return text.len();
+}
+
+
+// Returns whether the character can start an identifier.
+fn is_identifier_start(c: char) -> bool {
+    UnicodeXID::is_xid_start(c)
+}
+
+
+// Finds the extent of the string literal at the start of the text.
+// Returns the length in bytes, and whether the string was properly
+// closed.  If it wasn't, the literal extends to the end of the text.
+fn scan_string_literal(text: &str) -> (usize, bool) {
+    let mut last_was_esc = true;
+    for (b, c) in text.char_indices() {
+        if last_was_esc {
+            last_was_esc = false;
+        }
+        else {
+            if c == '\\' {
+                last_was_esc = true;
+            }
+            else if c == '"' {
+                return (b + 1, true);
+            }
+        }
+    }
+    return (text.len(), false);
+}
+
+
+// Finds the extent of the raw string literal at the start of the text.
+// Returns the length in bytes, and whether the string was properly
+// closed.  If it wasn't, the literal extends to the end of the text.
+fn scan_raw_string_literal(text: &str) -> (usize, bool) {
+    let mut stage = 0i32;
+    let mut start_tick_count = 0;
+    let mut tick_count = 0;
+    for (b, c) in text.char_indices() {
+        if stage == 0 {
+            // Get the starting tick count
+            if c == '\'' {
+                start_tick_count += 1;
+            }
+            else {
+                stage = 1;
+            }
+        }
+        else if stage == 1 {
+            // Look for double-quotes
+            if c == '"' {
+                stage = 2;
+            }
+        }
+        else if stage == 2 {
+            // Count closing ticks
+            if c == '\'' {
+                tick_count += 1;
+                if tick_count == start_tick_count {
+                    return (b + 1, true);
+                }
+            }
+            else {
+                tick_count = 0;
+                stage = 1;
+            }
+        }
+    }
+    return (text.len(), false);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use source_map::{SourceMap, LineCol};
+    use token::{Token, TokenKind};
+    
+    fn span(text: &str, lo: usize) -> Span {
+        Span::new(FileId(0), lo, lo + text.len())
+    }
+    
+    fn tok(kind: TokenKind, text: &str, lo: usize) -> Token<'_> {
+        Token::new(kind, text, span(text, lo))
+    }
+    
+    fn ident(kind: TokenKind, text: &str, lo: usize, sym: Symbol) -> Token<'_> {
+        Token::new_identifier(kind, text, span(text, lo), sym)
+    }
+    
+    #[test]
+    fn idents_and_keywords_1() {
+        let text = "var hello";
+        let (tokens, _) = lex_str(text);
+        
+        assert_eq!(tokens[0], tok(TokenKind::KEY_Var, "var", 0));
+        assert_eq!(tokens[1], ident(TokenKind::Identifier, "hello", 4, Symbol::intern("hello")));
+        assert_eq!(tokens[2].kind, TokenKind::EOF);
+    }
+    
+    #[test]
+    fn idents_and_keywords_2() {
+        let text = "var a";
+        let (tokens, _) = lex_str(text);
+        
+        assert_eq!(tokens[0], tok(TokenKind::KEY_Var, "var", 0));
+        assert_eq!(tokens[1], ident(TokenKind::Identifier, "a", 4, Symbol::intern("a")));
+        assert_eq!(tokens[2].kind, TokenKind::EOF);
+    }
+    
+    #[test]
+    fn newlines() {
+        let text = "var\n \n   \n hello";
+        let (tokens, _) = lex_str(text);
+        
+        assert_eq!(tokens[0], tok(TokenKind::KEY_Var, "var", 0));
+        assert_eq!(tokens[1], tok(TokenKind::NewLine, "\n", 3));
+        assert_eq!(tokens[2], tok(TokenKind::NewLine, "\n", 5));
+        assert_eq!(tokens[3], tok(TokenKind::NewLine, "\n", 9));
+        assert_eq!(tokens[4], ident(TokenKind::Identifier, "hello", 11, Symbol::intern("hello")));
+        assert_eq!(tokens[5].kind, TokenKind::EOF);
+
+        let mut map = SourceMap::new();
+        map.add_file("test.rune", text.to_string());
+        let positions: Vec<_> = tokens.iter().map(|t| map.line_col(t.span)).collect();
+        assert_eq!(positions, vec![
+            LineCol { line: 0, column: 0 },
+            LineCol { line: 0, column: 3 },
+            LineCol { line: 1, column: 1 },
+            LineCol { line: 2, column: 3 },
+            LineCol { line: 3, column: 1 },
+            LineCol { line: 3, column: 6 },
+        ]);
+    }
+
+    #[test]
+    fn punctuation() {
+        let text = "{}()[]@.,:`$";
+        let (tokens, _) = lex_str(text);
+        
+        assert_eq!(tokens[0], tok(TokenKind::LCurly, "{", 0));
+        assert_eq!(tokens[1], tok(TokenKind::RCurly, "}", 1));
+        assert_eq!(tokens[2], tok(TokenKind::LParen, "(", 2));
+        assert_eq!(tokens[3], tok(TokenKind::RParen, ")", 3));
+        assert_eq!(tokens[4], tok(TokenKind::LSquare, "[", 4));
+        assert_eq!(tokens[5], tok(TokenKind::RSquare, "]", 5));
+        assert_eq!(tokens[6], tok(TokenKind::At, "@", 6));
+        assert_eq!(tokens[7], tok(TokenKind::Period, ".", 7));
+        assert_eq!(tokens[8], tok(TokenKind::Comma, ",", 8));
+        assert_eq!(tokens[9], tok(TokenKind::Colon, ":", 9));
+        assert_eq!(tokens[10], tok(TokenKind::BackTick, "`", 10));
+        assert_eq!(tokens[11], tok(TokenKind::Dollar, "$", 11));
+        assert_eq!(tokens[12].kind, TokenKind::EOF);
+    }
+    
+    #[test]
+    fn operator() {
+        let text = "- + / * % | & ! ~ ++-*&|%";
+        let (tokens, _) = lex_str(text);
+        
+        assert_eq!(tokens[0], tok(TokenKind::Operator, "-", 0));
+        assert_eq!(tokens[1], tok(TokenKind::Operator, "+", 2));
+        assert_eq!(tokens[2], tok(TokenKind::Operator, "/", 4));
+        assert_eq!(tokens[3], tok(TokenKind::Operator, "*", 6));
+        assert_eq!(tokens[4], tok(TokenKind::Operator, "%", 8));
+        assert_eq!(tokens[5], tok(TokenKind::Operator, "|", 10));
+        assert_eq!(tokens[6], tok(TokenKind::Operator, "&", 12));
+        assert_eq!(tokens[7], tok(TokenKind::Operator, "!", 14));
+        assert_eq!(tokens[8], tok(TokenKind::Operator, "~", 16));
+        assert_eq!(tokens[9], tok(TokenKind::Operator, "++", 18));
+        assert_eq!(tokens[10], tok(TokenKind::Operator, "-", 20));
+        assert_eq!(tokens[11], tok(TokenKind::Operator, "*", 21));
+        assert_eq!(tokens[12], tok(TokenKind::Operator, "&", 22));
+        assert_eq!(tokens[13], tok(TokenKind::Operator, "|", 23));
+        assert_eq!(tokens[14], tok(TokenKind::Operator, "%", 24));
+        assert_eq!(tokens[15].kind, TokenKind::EOF);
+    }
+    
+    #[test]
+    fn ints_and_reals() {
+        let text = "123 12.3";
+        let (tokens, errors) = lex_str(text);
+        
+        assert_eq!(tokens[0], tok(TokenKind::LIT_Int, "123", 0));
+        assert_eq!(tokens[1], tok(TokenKind::LIT_Real, "12.3", 4));
+        assert_eq!(tokens[2].kind, TokenKind::EOF);
+        assert!(errors.is_empty());
+    }
+    
+    #[test]
+    fn number_literals() {
+        let text = "0xFF_FF 0o17 0b1010 1_000u32 1.5e-3 2E10 1.0f32 1f64 1.foo";
+        let (tokens, errors) = lex_str(text);
+        let expected = [
+            tok(TokenKind::LIT_Int, "0xFF_FF", 0),
+            tok(TokenKind::LIT_Int, "0o17", 8),
+            tok(TokenKind::LIT_Int, "0b1010", 13),
+            tok(TokenKind::LIT_Int, "1_000u32", 20),
+            tok(TokenKind::LIT_Real, "1.5e-3", 29),
+            tok(TokenKind::LIT_Real, "2E10", 36),
+            tok(TokenKind::LIT_Real, "1.0f32", 41),
+            tok(TokenKind::LIT_Real, "1f64", 48),
+            tok(TokenKind::LIT_Int, "1", 53),
+            tok(TokenKind::Period, ".", 54),
+            ident(TokenKind::Identifier, "foo", 55, Symbol::intern("foo")),
+            tok(TokenKind::EOF, "", 58),
+        ];
+        
+        assert_eq!(&tokens.iter().cloned().collect::<Vec<_>>()[..], &expected[..]);
+        assert!(errors.is_empty());
+    }
+    
+    #[test]
+    fn number_literal_errors() {
+        let text = "0x 0b102 42abc 1.5u8";
+        let (tokens, errors) = lex_str(text);
+        
+        assert_eq!(tokens[0], tok(TokenKind::LIT_Int, "0x", 0));
+        assert_eq!(tokens[1], tok(TokenKind::LIT_Int, "0b102", 3));
+        assert_eq!(tokens[2], tok(TokenKind::LIT_Int, "42abc", 9));
+        assert_eq!(tokens[3], tok(TokenKind::LIT_Real, "1.5u8", 15));
+        assert_eq!(tokens[4].kind, TokenKind::EOF);
+        
+        let kinds: Vec<_> = errors.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![
+            LexErrorKind::MissingDigits,
+            LexErrorKind::InvalidDigit,
+            LexErrorKind::InvalidNumberSuffix,
+            LexErrorKind::InvalidNumberSuffix,
+        ]);
+        assert_eq!(errors[1].source, tokens[1].span);
+    }
+    
+    #[test]
+    fn comments() {
+        let text = "var hello# How's it going?\n";
+        let (tokens, _) = lex_str(text);
+        
+        assert_eq!(tokens[0], tok(TokenKind::KEY_Var, "var", 0));
+        assert_eq!(tokens[1], ident(TokenKind::Identifier, "hello", 4, Symbol::intern("hello")));
+        assert_eq!(tokens[2], tok(TokenKind::NewLine, "\n", 26));
+        assert_eq!(tokens[3].kind, TokenKind::EOF);
+    }
+    
+    #[test]
+    fn doc_comments() {
+        let text = "var hello#: How's it going?\n";
+        let (tokens, _) = lex_str(text);
+        
+        assert_eq!(tokens[0], tok(TokenKind::KEY_Var, "var", 0));
+        assert_eq!(tokens[1], ident(TokenKind::Identifier, "hello", 4, Symbol::intern("hello")));
+        assert_eq!(tokens[2], tok(TokenKind::DocComment, "#: How's it going?", 9));
+        assert_eq!(tokens[3], tok(TokenKind::NewLine, "\n", 27));
+        assert_eq!(tokens[4].kind, TokenKind::EOF);
+
+    }
+    
+    #[test]
+    fn string_literal_1() {
+        let text = r#"var"Suddenly there's \"a string!"hello"#;
+        let (tokens, _) = lex_str(text);
+        
+        assert_eq!(tokens[0], tok(TokenKind::KEY_Var, "var", 0));
+        assert_eq!(tokens[1], tok(TokenKind::LIT_String, r#""Suddenly there's \"a string!""#, 3));
+        assert_eq!(tokens[2], ident(TokenKind::Identifier, "hello", 33, Symbol::intern("hello")));
+        assert_eq!(tokens[3].kind, TokenKind::EOF);
+    }
+     
+    #[test]
+    fn raw_string_literal_1() {
+        let text = r#"var'"Suddenly there's "a raw string!"'hello"#;
+        let (tokens, _) = lex_str(text);
+        
+        assert_eq!(tokens[0], tok(TokenKind::KEY_Var, "var", 0));
+        assert_eq!(tokens[1], tok(TokenKind::LIT_RawString, r#"'"Suddenly there's "a raw string!"'"#, 3));
+        assert_eq!(tokens[2], ident(TokenKind::Identifier, "hello", 38, Symbol::intern("hello")));
+        assert_eq!(tokens[3].kind, TokenKind::EOF);
+    }
+    
+    #[test]
+    fn raw_string_literal_2() {
+        let text = r#"var''"Suddenly there's "'a raw string!"''hello"#;
+        let (tokens, _) = lex_str(text);
+        
+        assert_eq!(tokens[0], tok(TokenKind::KEY_Var, "var", 0));
+        assert_eq!(tokens[1], tok(TokenKind::LIT_RawString, r#"''"Suddenly there's "'a raw string!"''"#, 3));
+        assert_eq!(tokens[2], ident(TokenKind::Identifier, "hello", 41, Symbol::intern("hello")));
+        assert_eq!(tokens[3].kind, TokenKind::EOF);
+    }
+    
+    #[test]
+    fn unterminated_string_literal() {
+        let text = "var \"Suddenly there's\n a string";
+        let (tokens, errors) = lex_str(text);
+        
+        let ss = span("\"Suddenly there's\n a string", 4);
+        assert_eq!(tokens[1], Token::new(TokenKind::LIT_String, "\"Suddenly there's\n a string", ss));
+        assert_eq!(tokens[2].kind, TokenKind::EOF);
+        assert_eq!(errors, vec![LexError {kind: LexErrorKind::UnterminatedString, source: ss}]);
+    }
+    
+    #[test]
+    fn unterminated_string_literal_multibyte() {
+        let text = "\"größe";
+        let (tokens, errors) = lex_str(text);
+        
+        assert_eq!(tokens[0].text, text);
+        assert_eq!(tokens[1].kind, TokenKind::EOF);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, LexErrorKind::UnterminatedString);
+    }
+    
+    #[test]
+    fn unterminated_raw_string_literal() {
+        let text = "var ''\"Suddenly there's \"' a raw string";
+        let (tokens, errors) = lex_str(text);
+        
+        let ss = span("''\"Suddenly there's \"' a raw string", 4);
+        assert_eq!(tokens[1], Token::new(TokenKind::LIT_RawString, "''\"Suddenly there's \"' a raw string", ss));
+        assert_eq!(tokens[2].kind, TokenKind::EOF);
+        assert_eq!(errors, vec![LexError {kind: LexErrorKind::UnterminatedRawString, source: ss}]);
+    }
+    
+    #[test]
+    fn stray_characters() {
+        let text = "a ; b?";
+        let (tokens, errors) = lex_str(text);
+        
+        let ss1 = span(";", 2);
+        let ss2 = span("?", 5);
+        assert_eq!(tokens[0], ident(TokenKind::Identifier, "a", 0, Symbol::intern("a")));
+        assert_eq!(tokens[1], Token::new(TokenKind::Unknown, ";", ss1));
+        assert_eq!(tokens[2], ident(TokenKind::Identifier, "b", 4, Symbol::intern("b")));
+        assert_eq!(tokens[3], Token::new(TokenKind::Unknown, "?", ss2));
+        assert_eq!(tokens[4].kind, TokenKind::EOF);
+        assert_eq!(errors, vec![
+            LexError {kind: LexErrorKind::StrayCharacter, source: ss1},
+            LexError {kind: LexErrorKind::StrayCharacter, source: ss2},
+        ]);
+    }
+    
+    #[test]
+    fn no_errors() {
+        let text = "var a = \"hello\" # comment\n";
+        let (_, errors) = lex_str(text);
+        
+        assert!(errors.is_empty());
+    }
+    
+    #[test]
+    fn lexer_iterator() {
+        let text = "var a";
+        let mut lexer = Lexer::new(text);
+        
+        assert_eq!(lexer.next(), Some(tok(TokenKind::KEY_Var, "var", 0)));
+        assert_eq!(lexer.byte_offset(), 3);
+        assert_eq!(lexer.next(), Some(ident(TokenKind::Identifier, "a", 4, Symbol::intern("a"))));
+        assert_eq!(lexer.next().map(|t| t.kind), Some(TokenKind::EOF));
+        assert_eq!(lexer.next(), None);
+        assert_eq!(lexer.next(), None);
+    }
+    
+    #[test]
+    fn lexer_iterator_errors() {
+        let text = "a ; b";
+        let mut lexer = Lexer::new(text);
+        
+        lexer.next();
+        assert!(lexer.errors().is_empty());
+        lexer.next();
+        assert_eq!(lexer.errors().len(), 1);
+        assert_eq!(lexer.take_errors()[0].kind, LexErrorKind::StrayCharacter);
+        assert!(lexer.errors().is_empty());
+    }
+    
+    #[test]
+    fn lexer_resume() {
+        let text = "var a\n  val hello\nb";
+        
+        // Lex up through the first newline, and remember the position.
+        let mut lexer = Lexer::new(text);
+        lexer.next();
+        lexer.next();
+        lexer.next();
+        let offset = lexer.byte_offset();
+        assert_eq!(offset, 6);
+        
+        // Resuming from there gives the same tokens as lexing straight
+        // through.
+        let rest: Vec<_> = lexer.collect();
+        let mut map = SourceMap::new();
+        let file = map.add_file("main.rune", text.to_string());
+        let resumed: Vec<_> = Lexer::new_at(map.file(file), offset).collect();
+        assert_eq!(rest, resumed);
+        assert_eq!(resumed[0], tok(TokenKind::KEY_Val, "val", 8));
+        assert_eq!(resumed[3], ident(TokenKind::Identifier, "b", 18, Symbol::intern("b")));
+        assert_eq!(resumed[4].kind, TokenKind::EOF);
+    }
+    
+    #[test]
+    fn lexer_resume_with_operators() {
+        let text = "fn <+> [a: i32, b: i32] -> i32 (a)\nval c = a<+>b\n";
+        let mut map = SourceMap::new();
+        map.add_file("other.rune", String::new());
+        let file_id = map.add_file("main.rune", text.to_string());
+        let file = map.file(file_id);
+        let operators = OperatorTable::for_source(text);
+        let offset = text.find("val").unwrap();
+
+        let resumed: Vec<_> = Lexer::new_at_with_operators(file, offset, &operators).collect();
+        assert_eq!(resumed, Lexer::new_at(file, offset).collect::<Vec<_>>());
+        assert_eq!(resumed[4].text, "<+>");
+        assert_eq!(resumed[4].span, Span::new(file.id(), offset + 9, offset + 12));
+
+        // Without the declaration, the operator is split up
+        let builtin = OperatorTable::builtin();
+        let resumed: Vec<_> = Lexer::new_at_with_operators(file, offset, &builtin).collect();
+        assert_eq!(resumed[4].text, "<");
+    }
+    
+    #[test]
+    fn columns_non_ascii_string_literal() {
+        let text = "\"größe\" a\n\"速\n度😀\" b";
+        let (tokens, _) = lex_str(text);
+        
+        assert_eq!(tokens[1], ident(TokenKind::Identifier, "a", 10, Symbol::intern("a")));
+        assert_eq!(tokens[4], ident(TokenKind::Identifier, "b", 26, Symbol::intern("b")));
+        
+        let mut map = SourceMap::new();
+        let id = map.add_file("test.rune", text.to_string());
+        let file = map.file(id);
+        let b = tokens[4].span.lo as usize;
+        assert_eq!(file.line_col(b), LineCol { line: 2, column: 4 });
+        assert_eq!(file.byte_column(b), 9);
+        assert_eq!(file.char_column(b), 4);
+        assert_eq!(file.grapheme_column(b), 4);
+        assert_eq!(file.utf16_column(b), 5);
+    }
+    
+    #[test]
+    fn columns_non_ascii_identifiers() {
+        let text = "速度 x\u{301} y";
+        let (tokens, _) = lex_str(text);
+        let y = tokens[tokens.len() - 2];
+        
+        let mut map = SourceMap::new();
+        let id = map.add_file("test.rune", text.to_string());
+        let file = map.file(id);
+        assert_eq!(y.text, "y");
+        assert_eq!(y.span.lo, 11);
+        assert_eq!(file.grapheme_column(11), 5);
+        assert_eq!(file.char_column(11), 6);
+        assert_eq!(file.utf16_column(11), 6);
+    }
+    
+    #[test]
+    fn unicode_identifiers() {
+        let text = "var größe 速度 _Tö x2\u{301}";
+        let (tokens, errors) = lex_str(text);
+        
+        assert_eq!(tokens[1], ident(TokenKind::Identifier, "größe", 4, Symbol::intern("größe")));
+        assert_eq!(tokens[2], ident(TokenKind::Identifier, "速度", 12, Symbol::intern("速度")));
+        assert_eq!(tokens[3], ident(TokenKind::IdentifierGeneric, "_Tö", 19, Symbol::intern("_Tö")));
+        assert_eq!(tokens[4], ident(TokenKind::Identifier, "x2\u{301}", 24, Symbol::intern("x2\u{301}")));
+        assert_eq!(tokens[5].kind, TokenKind::EOF);
+        assert!(errors.is_empty());
+    }
+    
+    #[test]
+    fn non_identifier_characters() {
+        // Symbols and emoji aren't XID_Start.
+        let text = "a😀 ¬";
+        let (tokens, errors) = lex_str(text);
+        
+        assert_eq!(tokens[1], tok(TokenKind::Unknown, "😀", 1));
+        assert_eq!(tokens[2], tok(TokenKind::Unknown, "¬", 6));
+        assert_eq!(errors.len(), 2);
+    }
+    
+    #[test]
+    fn identifier_normalization() {
+        let composed = "gr\u{F6}\u{DF}e";
+        let decomposed = "gro\u{308}\u{DF}e";
+        
+        assert!(composed != decomposed);
+        assert_eq!(normalize_identifier(composed), normalize_identifier(decomposed));
+        assert_eq!(normalize_identifier(decomposed), composed);
+        match normalize_identifier(composed) {
+            Cow::Borrowed(_) => {},
+            Cow::Owned(_) => panic!("Already normalized text shouldn't be copied."),
+        }
+        
+        // The decomposed form still lexes as a single identifier.
+        let (tokens, _) = lex_str(decomposed);
+        assert_eq!(tokens[0], ident(TokenKind::Identifier, decomposed, 0, Symbol::intern(composed)));
+    }
+    
+    #[test]
+    fn confusable_identifier_warning() {
+        // The "а" here is Cyrillic, but otherwise it's all Latin.
+        let text = "val p\u{430}ypal = 1";
+        let (tokens, errors) = lex_str(text);
+        
+        let ss = span("p\u{430}ypal", 4);
+        assert_eq!(tokens[1], ident(TokenKind::Identifier, "p\u{430}ypal", 4, Symbol::intern("p\u{430}ypal")));
+        assert_eq!(errors, vec![LexError {kind: LexErrorKind::ConfusableIdentifier, source: ss}]);
+        assert_eq!(errors[0].kind.severity(), Severity::Warning);
+        
+        let diag = errors[0].to_diagnostic();
+        assert_eq!(diag.severity, Severity::Warning);
+        assert_eq!(diag.code, Some(codes::CONFUSABLE_IDENTIFIER));
+        assert_eq!(diag.message, "Identifier mixes scripts with confusable characters.");
+        assert_eq!(diag.primary_span(), Some(ss));
+    }
+    
+    #[test]
+    fn non_confusable_mixed_identifiers() {
+        // Single-script identifiers, and mixes that can't be confused with
+        // anything, are fine.
+        let text = "пример größe速度 ひらがな漢字カタカナ Straße";
+        let (_, errors) = lex_str(text);
+        
+        assert!(errors.is_empty());
+    }
+    
+    #[test]
+    fn directives() {
+        let text = "%import std.io\n  %foo\na %b\n%\n";
+        let (tokens, errors) = lex_str(text);
+        assert_eq!(errors.len(), 0);
+        
+        assert_eq!(tokens[0], tok(TokenKind::Directive, "%import", 0));
+        assert_eq!(tokens[1], ident(TokenKind::Identifier, "std", 8, Symbol::intern("std")));
+        assert_eq!(tokens[5], tok(TokenKind::Directive, "%foo", 17));
+        
+        // Not at the start of a line, or without a name
+        assert_eq!(tokens[8], tok(TokenKind::Operator, "%", 24));
+        assert_eq!(tokens[11], tok(TokenKind::Operator, "%", 27));
+    }
+    
+    #[test]
+    fn directive_resume() {
+        let text = "a\n  %foo";
+        let mut map = SourceMap::new();
+        let file = map.add_file("main.rune", text.to_string());
+        let mut lexer = Lexer::new_at(map.file(file), 4);
+        assert_eq!(lexer.next(), Some(tok(TokenKind::Directive, "%foo", 4)));
+    }
+    
+    #[test]
+    fn codepoint_literals() {
+        let text = r#"'a' '速' '\'' '\u{1F600}' '"raw"'"#;
+        let (tokens, errors) = lex_str(text);
+        
+        assert_eq!(tokens[0], tok(TokenKind::LIT_Codepoint, "'a'", 0));
+        assert_eq!(tokens[1], tok(TokenKind::LIT_Codepoint, "'速'", 4));
+        assert_eq!(tokens[2], tok(TokenKind::LIT_Codepoint, r"'\''", 10));
+        assert_eq!(tokens[3], tok(TokenKind::LIT_Codepoint, r"'\u{1F600}'", 15));
+        assert_eq!(tokens[4], tok(TokenKind::LIT_RawString, r#"'"raw"'"#, 27));
+        assert_eq!(tokens[5].kind, TokenKind::EOF);
+        assert!(errors.is_empty());
+    }
+    
+    #[test]
+    fn unterminated_codepoint_literal() {
+        let text = "'a\nb";
+        let (tokens, errors) = lex_str(text);
+        
+        let ss = span("'a", 0);
+        assert_eq!(tokens[0], Token::new(TokenKind::LIT_Codepoint, "'a", ss));
+        assert_eq!(tokens[1], tok(TokenKind::NewLine, "\n", 2));
+        assert_eq!(tokens[2], ident(TokenKind::Identifier, "b", 3, Symbol::intern("b")));
+        assert_eq!(errors, vec![LexError {kind: LexErrorKind::UnterminatedCodepoint, source: ss}]);
+    }
+}
\ No newline at end of file
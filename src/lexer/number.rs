@@ -0,0 +1,342 @@
+//! Number literal syntax.
+//!
+//! Number literals are made of an optional base prefix (`0x`, `0o` or
+//! `0b`), digits, and an optional type suffix such as `u8` or `f32`.
+//! Digits can be separated with underscores.  Decimal literals can also
+//! have a fractional part and/or an exponent, which makes them real
+//! number literals:
+//!
+//! ```text
+//! 42  0xFF_FF  0o777  0b1010  1_000_000u32
+//! 1.5  1.5e-3  2E10  1.0f32  1f64
+//! ```
+
+use super::{Lexeme, LexErrorKind, scan_identifier};
+
+/// The built-in number types that can be given as a literal suffix.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum NumberType {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    F16,
+    F32,
+    F64,
+}
+
+impl NumberType {
+    /// Returns the number type named by a literal suffix, if any.
+    pub fn from_suffix(suffix: &str) -> Option<NumberType> {
+        match suffix {
+            "i8" => Some(NumberType::I8),
+            "i16" => Some(NumberType::I16),
+            "i32" => Some(NumberType::I32),
+            "i64" => Some(NumberType::I64),
+            "u8" => Some(NumberType::U8),
+            "u16" => Some(NumberType::U16),
+            "u32" => Some(NumberType::U32),
+            "u64" => Some(NumberType::U64),
+            "f16" => Some(NumberType::F16),
+            "f32" => Some(NumberType::F32),
+            "f64" => Some(NumberType::F64),
+            _ => None,
+        }
+    }
+    
+    /// The name of the type, as written in source code.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            NumberType::I8 => "i8",
+            NumberType::I16 => "i16",
+            NumberType::I32 => "i32",
+            NumberType::I64 => "i64",
+            NumberType::U8 => "u8",
+            NumberType::U16 => "u16",
+            NumberType::U32 => "u32",
+            NumberType::U64 => "u64",
+            NumberType::F16 => "f16",
+            NumberType::F32 => "f32",
+            NumberType::F64 => "f64",
+        }
+    }
+    
+    pub fn is_integer(&self) -> bool {
+        !matches!(*self, NumberType::F16 | NumberType::F32 | NumberType::F64)
+    }
+    
+    /// The largest value of an integer type.  Returns None for real
+    /// number types.
+    pub fn max_integer(&self) -> Option<u64> {
+        match *self {
+            NumberType::I8 => Some(i8::MAX as u64),
+            NumberType::I16 => Some(i16::MAX as u64),
+            NumberType::I32 => Some(i32::MAX as u64),
+            NumberType::I64 => Some(i64::MAX as u64),
+            NumberType::U8 => Some(u8::MAX as u64),
+            NumberType::U16 => Some(u16::MAX as u64),
+            NumberType::U32 => Some(u32::MAX as u64),
+            NumberType::U64 => Some(u64::MAX),
+            _ => None,
+        }
+    }
+    
+    /// The largest finite value of a real number type.  Returns None for
+    /// integer types.
+    pub fn max_real(&self) -> Option<f64> {
+        match *self {
+            NumberType::F16 => Some(65504.0),
+            NumberType::F32 => Some(f32::MAX as f64),
+            NumberType::F64 => Some(f64::MAX),
+            _ => None,
+        }
+    }
+
+
+    /// The smallest value that rounds to infinity in a real number type,
+    /// which is halfway between its largest finite value and the next
+    /// value up.  Returns None for integer types.
+    pub fn real_overflow_threshold(&self) -> Option<f64> {
+        match *self {
+            NumberType::F16 => Some(65504.0 + 16.0),
+            NumberType::F32 => Some(f32::MAX as f64 + 2f64.powi(103)),
+            NumberType::F64 => Some(f64::INFINITY),
+            _ => None,
+        }
+    }
+}
+
+
+/// A number literal split into its parts.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct NumberParts<'a> {
+    /// 2, 8, 10 or 16.
+    pub radix: u32,
+    
+    /// Everything between the base prefix and the suffix, including any
+    /// underscores, fractional part and exponent.
+    pub digits: &'a str,
+    
+    /// The type suffix as written, which may be empty.
+    pub suffix: &'a str,
+}
+
+impl<'a> NumberParts<'a> {
+    /// Whether the literal is a real number literal rather than an integer
+    /// literal.
+    pub fn is_real(&self) -> bool {
+        if self.radix != 10 {
+            return false;
+        }
+        if self.digits.contains(['.', 'e', 'E']) {
+            return true;
+        }
+        return NumberType::from_suffix(self.suffix).is_some_and(|t| !t.is_integer());
+    }
+    
+    /// The type given by the suffix, if there is a valid one.
+    pub fn suffix_type(&self) -> Option<NumberType> {
+        NumberType::from_suffix(self.suffix)
+    }
+    
+    /// The digits with the underscores removed.
+    pub fn clean_digits(&self) -> String {
+        self.digits.chars().filter(|&c| c != '_').collect()
+    }
+}
+
+
+/// Splits a number literal (as lexed by the lexer) into its parts.
+pub fn split_number<'a>(text: &'a str) -> NumberParts<'a> {
+    let core_len = scan_number_core(text);
+    let (radix, prefix_len) = radix_prefix(text);
+    return NumberParts {
+        radix: radix,
+        digits: &text[prefix_len..core_len],
+        suffix: &text[core_len..],
+    };
+}
+
+
+/// Checks a number literal for problems that can be found without
+/// knowing its value.
+pub fn check_number(text: &str) -> Option<LexErrorKind> {
+    let parts = split_number(text);
+    
+    if !parts.digits.contains(|c| c != '_') {
+        return Some(LexErrorKind::MissingDigits);
+    }
+    
+    if parts.digits.contains(|c: char| c != '_' && !c.is_digit(parts.radix)) && parts.radix != 10 {
+        return Some(LexErrorKind::InvalidDigit);
+    }
+    
+    if parts.suffix.len() > 0 {
+        match parts.suffix_type() {
+            None => return Some(LexErrorKind::InvalidNumberSuffix),
+            Some(t) => {
+                // In hex, "e" and "E" are digits rather than exponents
+                let is_fractional = parts.radix == 10 && parts.digits.contains(['.', 'e', 'E']);
+                if (t.is_integer() && is_fractional) || (!t.is_integer() && parts.radix != 10) {
+                    return Some(LexErrorKind::InvalidNumberSuffix);
+                }
+            }
+        }
+    }
+    
+    return None;
+}
+
+
+/// Finds the extent of the number literal at the start of `text`, which
+/// must start with a digit.  Returns whether it's an integer or real
+/// literal, and its length in bytes.
+pub(super) fn scan_number(text: &str) -> (Lexeme, usize) {
+    finish_number(text, scan_number_core(text))
+}
+
+
+/// Given the length of the prefix and digits of the number literal at
+/// the start of `text`, finds the extent of the rest of it (i.e. the
+/// suffix).  Returns whether it's an integer or real literal, and its
+/// length in bytes.
+pub(super) fn finish_number(text: &str, core_len: usize) -> (Lexeme, usize) {
+    let len = scan_identifier(text, core_len);
+    if split_number(&text[..len]).is_real() {
+        return (Lexeme::Real, len);
+    }
+    return (Lexeme::Int, len);
+}
+
+
+// Returns the radix of the number literal and the length of its prefix.
+fn radix_prefix(text: &str) -> (u32, usize) {
+    if text.starts_with("0x") {
+        (16, 2)
+    }
+    else if text.starts_with("0o") {
+        (8, 2)
+    }
+    else if text.starts_with("0b") {
+        (2, 2)
+    }
+    else {
+        (10, 0)
+    }
+}
+
+
+// Returns the length of the prefix and digits of the number literal at
+// the start of `text`, i.e. everything but the suffix.
+fn scan_number_core(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let (radix, prefix_len) = radix_prefix(text);
+    
+    // Non-decimal integers.  Octal and binary literals consume all
+    // decimal digits, so that e.g. 0b102 is reported as an invalid digit
+    // rather than having a "2" suffix.
+    if radix == 16 {
+        return run_length(bytes, prefix_len, |b| b == b'_' || (b as char).is_ascii_hexdigit());
+    }
+    if radix != 10 {
+        return run_length(bytes, prefix_len, is_digit_or_underscore);
+    }
+    
+    // Decimal integer part
+    let mut i = run_length(bytes, 0, is_digit_or_underscore);
+    
+    // Fractional part, which must start with a digit so that e.g. 1.foo
+    // is still a method call on 1.
+    if bytes.get(i) == Some(&b'.') && bytes.get(i + 1).is_some_and(|&b| is_digit(b)) {
+        i = run_length(bytes, i + 1, is_digit_or_underscore);
+    }
+    
+    // Exponent, which must have at least one digit
+    if bytes.get(i) == Some(&b'e') || bytes.get(i) == Some(&b'E') {
+        let mut j = i + 1;
+        if bytes.get(j) == Some(&b'+') || bytes.get(j) == Some(&b'-') {
+            j += 1;
+        }
+        if bytes.get(j).is_some_and(|&b| is_digit(b)) {
+            i = run_length(bytes, j, is_digit_or_underscore);
+        }
+    }
+    
+    return i;
+}
+
+
+fn run_length<F: Fn(u8) -> bool>(bytes: &[u8], start: usize, pred: F) -> usize {
+    let mut i = start;
+    while i < bytes.len() && pred(bytes[i]) {
+        i += 1;
+    }
+    return i;
+}
+
+fn is_digit(b: u8) -> bool {
+    b.is_ascii_digit()
+}
+
+fn is_digit_or_underscore(b: u8) -> bool {
+    is_digit(b) || b == b'_'
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::{Lexeme, LexErrorKind};
+    
+    #[test]
+    fn split() {
+        assert_eq!(split_number("0xFF_FFu16"), NumberParts {radix: 16, digits: "FF_FF", suffix: "u16"});
+        assert_eq!(split_number("0o17"), NumberParts {radix: 8, digits: "17", suffix: ""});
+        assert_eq!(split_number("0b10_1"), NumberParts {radix: 2, digits: "10_1", suffix: ""});
+        assert_eq!(split_number("1_000i64"), NumberParts {radix: 10, digits: "1_000", suffix: "i64"});
+        assert_eq!(split_number("1.5e-3f32"), NumberParts {radix: 10, digits: "1.5e-3", suffix: "f32"});
+        assert_eq!(split_number("0x1f32"), NumberParts {radix: 16, digits: "1f32", suffix: ""});
+    }
+    
+    #[test]
+    fn scan() {
+        assert_eq!(scan_number("42 "), (Lexeme::Int, 2));
+        assert_eq!(scan_number("42u8+"), (Lexeme::Int, 4));
+        assert_eq!(scan_number("1f32"), (Lexeme::Real, 4));
+        assert_eq!(scan_number("1.5"), (Lexeme::Real, 3));
+        assert_eq!(scan_number("1.foo"), (Lexeme::Int, 1));
+        assert_eq!(scan_number("1e10"), (Lexeme::Real, 4));
+        assert_eq!(scan_number("1E+10"), (Lexeme::Real, 5));
+        assert_eq!(scan_number("1e-"), (Lexeme::Int, 2));
+        assert_eq!(scan_number("1.2.3"), (Lexeme::Real, 3));
+        assert_eq!(scan_number("0x1.5"), (Lexeme::Int, 3));
+    }
+    
+    #[test]
+    fn check() {
+        assert_eq!(check_number("0xFF"), None);
+        assert_eq!(check_number("1_000u32"), None);
+        assert_eq!(check_number("1.5e-3f64"), None);
+        assert_eq!(check_number("1f16"), None);
+        assert_eq!(check_number("0x"), Some(LexErrorKind::MissingDigits));
+        assert_eq!(check_number("0b_"), Some(LexErrorKind::MissingDigits));
+        assert_eq!(check_number("0b102"), Some(LexErrorKind::InvalidDigit));
+        assert_eq!(check_number("0o8"), Some(LexErrorKind::InvalidDigit));
+        assert_eq!(check_number("42abc"), Some(LexErrorKind::InvalidNumberSuffix));
+        assert_eq!(check_number("1.0u8"), Some(LexErrorKind::InvalidNumberSuffix));
+        assert_eq!(check_number("0b1f32"), Some(LexErrorKind::InvalidNumberSuffix));
+        assert_eq!(check_number("1e3u8"), Some(LexErrorKind::InvalidNumberSuffix));
+    }
+
+    #[test]
+    fn hex_e_digits() {
+        assert_eq!(check_number("0xFEu8"), None);
+        assert_eq!(check_number("0x1eu16"), None);
+        assert_eq!(check_number("0xE_Ei32"), None);
+        assert_eq!(split_number("0xFEu8"), NumberParts {radix: 16, digits: "FE", suffix: "u8"});
+    }
+}
@@ -0,0 +1,267 @@
+//! Operator tokenization.
+//!
+//! Operators are made of the characters `-+/*%|&!~=<>`.  A run of those
+//! characters is split into operators by maximal munch against a table
+//! of known operators: the longest known operator at the start of the
+//! run is taken, and then the rest of the run is split the same way.
+//! For example `a=-1` is `a`, `=`, `-`, `1`, since `=-` isn't a known
+//! operator.
+//!
+//! The table starts out with the built-in operators, and is extended
+//! with any operators the source text declares functions for with the
+//! `fn` sugar:
+//!
+//! ```text
+//! fn <+> [a: i32, b: i32] -> i32 ( ... )
+//! ```
+//!
+//! Whether an operator is used as a prefix, infix, or postfix operator
+//! is determined by the whitespace around it.  See `fixity()`.
+
+use std::collections::HashSet;
+use source_map::Span;
+use super::{Lexeme, hand_written_backend, normalize_identifier};
+
+/// The built-in operators.
+pub const BUILTIN_OPERATORS: &'static [&'static str] = &[
+    // Arithmetic
+    "+", "-", "*", "/", "%",
+    "++", "--",
+
+    // Bitwise and logical
+    "&", "|", "~", "!", "<<", ">>",
+    "&&", "||",
+
+    // Comparison
+    "==", "!=", "<", ">", "<=", ">=",
+
+    // Assignment
+    "=", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "<<=", ">>=",
+
+    // Function return type
+    "->",
+];
+
+
+/// A table of known operators, used to split runs of operator characters
+/// into individual operators.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct OperatorTable {
+    operators: HashSet<String>,
+    max_len: usize,
+}
+
+impl OperatorTable {
+    /// Creates a table with just the built-in operators.
+    pub fn builtin() -> OperatorTable {
+        let mut table = OperatorTable {
+            operators: HashSet::new(),
+            max_len: 0,
+        };
+        for op in BUILTIN_OPERATORS {
+            table.add(op);
+        }
+        table
+    }
+
+
+    /// Creates a table with the built-in operators plus the operators
+    /// declared in `text`.
+    pub fn for_source(text: &str) -> OperatorTable {
+        let mut table = OperatorTable::builtin();
+        table.add_declared(text);
+        table
+    }
+
+
+    /// Adds an operator to the table.
+    pub fn add(&mut self, op: &str) {
+        if op.len() > self.max_len {
+            self.max_len = op.len();
+        }
+        self.operators.insert(op.to_string());
+    }
+
+
+    /// Whether `op` is a known operator.
+    pub fn contains(&self, op: &str) -> bool {
+        self.operators.contains(op)
+    }
+
+
+    /// Adds the operators declared with `fn <op> [...]` in `text`.
+    pub fn add_declared(&mut self, text: &str) {
+        let mut remaining = text;
+        let mut after_fn = false;
+        while remaining.len() > 0 {
+            let (lexeme, n) = hand_written_backend::next_lexeme(remaining);
+            let lexeme_text = &remaining[..n];
+            match lexeme {
+                Lexeme::Whitespace => {},
+                Lexeme::Operator if after_fn => {
+                    self.add(lexeme_text);
+                    after_fn = false;
+                },
+                Lexeme::IdentOrKeyword => after_fn = normalize_identifier(lexeme_text) == "fn",
+                _ => after_fn = false,
+            }
+            remaining = &remaining[n..];
+        }
+    }
+
+
+    /// Returns the length in bytes of the operator at the start of `run`,
+    /// which must be a run of operator characters.
+    ///
+    /// This is the longest known operator that `run` starts with.  If no
+    /// known operator matches, the first character is taken on its own.
+    pub fn longest_match(&self, run: &str) -> usize {
+        let mut len = if run.len() < self.max_len { run.len() } else { self.max_len };
+        while len > 1 {
+            if self.operators.contains(&run[..len]) {
+                return len;
+            }
+            len -= 1;
+        }
+        return 1;
+    }
+}
+
+
+//=====================================
+/// How an operator is being used.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Fixity {
+    Prefix,
+    Infix,
+    Postfix,
+}
+
+
+/// Determines how an operator token is used, based on the whitespace
+/// around it.
+///
+/// Each side of an operator is either open (whitespace, the start or end
+/// of the text, a bracket that opens or closes a group on that side, a
+/// comma or colon, or a comment) or bound to an operand.  An operator
+/// that is bound on just the right is prefix, e.g. `-a`, bound on just
+/// the left is postfix, e.g. `a++ `, and otherwise is infix, e.g. `a + b`
+/// or `a+b`.
+///
+/// An operator directly after another operator is treated as open on its
+/// left, so that `a=-1` is `a = (-1)`.
+///
+/// `text` is the full source text that the operator's span is in.
+pub fn fixity(text: &str, span: Span) -> Fixity {
+    let before = text[..(span.lo as usize)].chars().next_back();
+    let after = text[(span.hi as usize)..].chars().next();
+
+    let left_open = match before {
+        None => true,
+        Some(c) => c.is_whitespace() || "([{,:".contains(c) || is_operator_char(c),
+    };
+    let right_open = match after {
+        None => true,
+        Some(c) => c.is_whitespace() || ")]},:#".contains(c),
+    };
+
+    match (left_open, right_open) {
+        (true, false) => Fixity::Prefix,
+        (false, true) => Fixity::Postfix,
+        _ => Fixity::Infix,
+    }
+}
+
+
+/// Whether a character can be part of an operator.
+pub fn is_operator_char(c: char) -> bool {
+    "-+/*%|&!~=<>".contains(c)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::lex_str;
+    use token::TokenKind;
+
+    // Lexes `text`, returning the operators in it along with their fixity.
+    fn operators(text: &str) -> Vec<(String, Fixity)> {
+        lex_str(text).0.iter().filter_map(|t| {
+            if t.kind == TokenKind::Operator { Some((t.text().to_string(), fixity(text, t.span))) } else { None }
+        }).collect()
+    }
+
+    fn op(text: &str, fixity: Fixity) -> (String, Fixity) {
+        (text.to_string(), fixity)
+    }
+
+    #[test]
+    fn longest_match() {
+        let table = OperatorTable::builtin();
+        assert_eq!(table.longest_match("+"), 1);
+        assert_eq!(table.longest_match("++"), 2);
+        assert_eq!(table.longest_match("+++"), 2);
+        assert_eq!(table.longest_match("<<="), 3);
+        assert_eq!(table.longest_match("=-"), 1);
+        assert_eq!(table.longest_match("&|"), 1);
+    }
+
+    #[test]
+    fn assign_negative() {
+        assert_eq!(operators("a=-1"), vec![op("=", Fixity::Infix), op("-", Fixity::Prefix)]);
+        assert_eq!(operators("a = -1"), vec![op("=", Fixity::Infix), op("-", Fixity::Prefix)]);
+    }
+
+    #[test]
+    fn prefix_increment() {
+        assert_eq!(operators("    ++self.size\n"), vec![op("++", Fixity::Prefix)]);
+        assert_eq!(operators("a = ++b"), vec![op("=", Fixity::Infix), op("++", Fixity::Prefix)]);
+    }
+
+    #[test]
+    fn postfix() {
+        assert_eq!(operators("a++ + b"), vec![op("++", Fixity::Postfix), op("+", Fixity::Infix)]);
+        assert_eq!(operators("f[a++]"), vec![op("++", Fixity::Postfix)]);
+        assert_eq!(operators("a++  # Increment"), vec![op("++", Fixity::Postfix)]);
+    }
+
+    #[test]
+    fn infix() {
+        assert_eq!(operators("a + b"), vec![op("+", Fixity::Infix)]);
+        assert_eq!(operators("a+b"), vec![op("+", Fixity::Infix)]);
+        assert_eq!(operators("a<=-b"), vec![op("<=", Fixity::Infix), op("-", Fixity::Prefix)]);
+        assert_eq!(operators("a+++b"), vec![op("++", Fixity::Infix), op("+", Fixity::Prefix)]);
+    }
+
+    #[test]
+    fn prefix_after_open_bracket() {
+        assert_eq!(operators("(-a)"), vec![op("-", Fixity::Prefix)]);
+        assert_eq!(operators("f[a, !b]"), vec![op("!", Fixity::Prefix)]);
+        assert_eq!(operators("-a"), vec![op("-", Fixity::Prefix)]);
+    }
+
+    #[test]
+    fn user_defined_operators() {
+        let text = "a <+> b\nfn <+> [a: i32, b: i32] -> i32 ( )\nc<+>-d";
+        assert_eq!(operators(text), vec![
+            op("<+>", Fixity::Infix),
+            op("<+>", Fixity::Infix),
+            op("->", Fixity::Infix),
+            op("<+>", Fixity::Infix),
+            op("-", Fixity::Prefix),
+        ]);
+
+        // Without the declaration it's split up
+        let ops: Vec<_> = operators("a <+> b").into_iter().map(|(op, _)| op).collect();
+        assert_eq!(ops, vec!["<", "+", ">"]);
+    }
+
+    #[test]
+    fn declared_operators() {
+        let mut table = OperatorTable::builtin();
+        table.add_declared("fn +++ [a: i32] -> i32 ()\nfn foo [] ()\nfn[] ()\nval a = b ** c");
+        assert!(table.contains("+++"));
+        assert!(!table.contains("**"));
+    }
+}
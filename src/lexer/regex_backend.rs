@@ -0,0 +1,115 @@
+//! The original, regex-driven lexer backend.
+//!
+//! Each regex is tried in turn against the start of the remaining text,
+//! and the first one that matches determines the lexeme.
+
+use regex::Regex;
+use super::number;
+use super::{Lexeme, scan_string_literal, scan_raw_string_literal, scan_codepoint_literal, scan_identifier, is_identifier_start};
+
+// Our regexes, built once for the whole process.
+lazy_static! {
+    // Non-newline whitespace
+    static ref RE_WHITESPACE: Regex = Regex::new(r"[ \t]+").unwrap();
+
+    // A single newline
+    static ref RE_NEWLINE: Regex = Regex::new(r"(\r\n|\r|\n)").unwrap();
+
+    // A comment
+    static ref RE_COMMENT: Regex = Regex::new(r"#[^\r\n]*").unwrap();
+
+    // A doc comment
+    static ref RE_DOC_COMMENT: Regex = Regex::new(r"#:[^\r\n]*").unwrap();
+
+    // Literals.  These only match the prefix and digits of a number
+    // literal, the suffix is found separately.
+    static ref RE_RADIX_INT: Regex = Regex::new(r"0x[0-9a-fA-F_]*|0o[0-9_]*|0b[0-9_]*").unwrap();
+    static ref RE_DECIMAL: Regex = Regex::new(r"[0-9][0-9_]*(\.[0-9][0-9_]*)?([eE][+-]?[0-9][0-9_]*)?").unwrap();
+    static ref RE_RAW_STRING_START: Regex = Regex::new("'+\"").unwrap();
+
+    // Operators
+    static ref RE_OPERATOR: Regex = Regex::new(r"[-+/*%|&!~=<>]+").unwrap();
+
+    // Punctuation
+    static ref RE_PUNCTUATION: Regex = Regex::new(r"[()\[\]{},:@.`$]").unwrap();
+}
+
+
+/// Finds the lexeme at the start of `text`, which must not be empty.
+/// Returns its kind and its length in bytes.
+pub fn next_lexeme(text: &str) -> (Lexeme, usize) {
+    // Newline
+    if let Some((0, n)) = RE_NEWLINE.find(text) {
+        return (Lexeme::NewLine, n);
+    }
+    
+    // Doc comment
+    else if let Some((0, n)) = RE_DOC_COMMENT.find(text) {
+        return (Lexeme::DocComment, n);
+    }
+    
+    // Comment
+    else if let Some((0, n)) = RE_COMMENT.find(text) {
+        return (Lexeme::Comment, n);
+    }
+    
+    // White space
+    else if let Some((0, n)) = RE_WHITESPACE.find(text) {
+        return (Lexeme::Whitespace, n);
+    }
+    
+    // Punctuation
+    else if let Some((0, n)) = RE_PUNCTUATION.find(text) {
+        return (Lexeme::Punctuation, n);
+    }
+    
+    // Operators
+    else if let Some((0, n)) = RE_OPERATOR.find(text) {
+        return (Lexeme::Operator, n);
+    }
+    
+    // Integer literal with a base prefix
+    else if let Some((0, n)) = RE_RADIX_INT.find(text) {
+        return number::finish_number(text, n);
+    }
+    
+    // Decimal integer or real number literal
+    else if let Some((0, n)) = RE_DECIMAL.find(text) {
+        return number::finish_number(text, n);
+    }
+    
+    // String literal
+    else if text.starts_with("\"") {
+        let (n, terminated) = scan_string_literal(text);
+        return (Lexeme::String { terminated: terminated }, n);
+    }
+    
+    // Raw string literal
+    else if let Some((0, _)) = RE_RAW_STRING_START.find(text) {
+        let (n, terminated) = scan_raw_string_literal(text);
+        return (Lexeme::RawString { terminated: terminated }, n);
+    }
+    
+    // Codepoint literal
+    else if text.starts_with("'") {
+        let (n, terminated) = scan_codepoint_literal(text);
+        return (Lexeme::Codepoint { terminated: terminated }, n);
+    }
+    
+    // Identifier or keyword.  The regex crate doesn't know about the
+    // XID_Start/XID_Continue properties, so identifiers are scanned by
+    // hand here as well.
+    else if text.chars().next().is_some_and(is_identifier_start) {
+        return (Lexeme::IdentOrKeyword, scan_identifier(text, 0));
+    }
+    
+    // Identifier of a generic parameter
+    else if text.starts_with("_") && text[1..].chars().next().is_some_and(is_identifier_start) {
+        return (Lexeme::IdentGeneric, scan_identifier(text, 1));
+    }
+    
+    // Unknown input text, consume a single character
+    else {
+        return (Lexeme::Unknown, text.chars().next().unwrap().len_utf8());
+    }
+}
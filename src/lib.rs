@@ -0,0 +1,48 @@
+//! The Rune compiler, as a library.
+//!
+//! The `rune` binary is a thin wrapper around this crate, and tools such as
+//! editors and linters can use it the same way.  Compiling a file goes
+//! through these stages:
+//!
+//! 1. Add the file to a `SourceMap`, which gives it a `FileId`.  Spans
+//!    refer to files by id, and the map turns them back into file names,
+//!    lines and columns.
+//! 2. Lex the file into tokens with `lexer::lex_file()`.
+//! 3. Parse the tokens with the functions in `parser`.
+//! 4. Turn the lex and parse errors into `Diagnostic`s with their
+//!    `to_diagnostic()` methods, and render them with
+//!    `diagnostics::render()`.
+//!
+//! ```
+//! use rune::source_map::SourceMap;
+//! use rune::lexer::lex_file;
+//! use rune::diagnostics::{render, ErrorFormat};
+//!
+//! let mut map = SourceMap::new();
+//! let id = map.add_file("main.rune", "val a = 0x".to_string());
+//! let (_tokens, errors) = lex_file(map.file(id));
+//! let diags: Vec<_> = errors.iter().map(|e| e.to_diagnostic()).collect();
+//! let rendered = render(ErrorFormat::Human, &diags, &map, false);
+//! assert!(rendered.starts_with("error[R0005]: Missing digits"));
+//! ```
+
+// The code base's own style, which clippy disagrees with
+#![allow(clippy::needless_return, clippy::redundant_field_names, clippy::get_first,
+         clippy::redundant_static_lifetimes, clippy::len_zero)]
+
+extern crate regex;
+extern crate rustc_serialize;
+extern crate unicode_segmentation;
+extern crate unicode_xid;
+extern crate unicode_normalization;
+extern crate unicode_security;
+#[macro_use]
+extern crate lazy_static;
+
+pub mod source_map;
+pub mod diagnostics;
+pub mod symbol;
+pub mod token;
+pub mod lexer;
+pub mod parser;
+pub mod formatter;
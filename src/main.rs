@@ -1,60 +1,271 @@
-extern crate regex;
-extern crate docopt;
-extern crate rustc_serialize;
+//! The `rune` command line tool, a wrapper around the `rune` library.
+
+// The code base's own style, which clippy disagrees with
+#![allow(clippy::needless_return, clippy::redundant_field_names, clippy::get_first,
+         clippy::redundant_static_lifetimes, clippy::len_zero)]
 
-mod source_span;
-mod token;
-mod lexer;
-mod parser;
+extern crate docopt;
+extern crate rune;
 
-use std::path::Path;
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{self, Read, Write};
+use std::process;
 use docopt::Docopt;
-use lexer::lex_str;
+use rune::source_map::{FileId, SourceMap};
+use rune::diagnostics::{self, codes, Diagnostic, ErrorFormat, Severity};
+use rune::lexer::lex_file;
+use rune::token::TokenKind;
+use rune::parser::{check_literals, parse_directives, parse_file};
+use rune::formatter::format_source;
 
 // Usage documentation string
 static USAGE: &'static str = "
-Usage: rune [options] [<file>]
-       rune --help
+Usage: rune lex [options] <file>...
+       rune parse [options] [--dump-ast] <file>...
+       rune check [options] <file>...
+       rune fmt [options] [--check] <file>...
+       rune run [options] <file>...
+       rune --explain <code>
+       rune (-h | --help)
+       rune --version
+
+Commands:
+    lex         Print the tokens of the files
+    parse       Parse the files
+    check       Check the files for errors, without running them
+    fmt         Format the files in place
+    run         Run a program
+
+A <file> of \"-\" reads from stdin.
 
 Options:
-    -h, --help  Show this message
+    -h, --help                Show this message
+    --version                 Show the version
+    --explain <code>          Explain an error code, e.g. R0001
+    --dump-ast                Print the parse tree
+    --check                   Don't format, but fail if formatting would
+                              change a file
+    --color                   Color error messages
+    --error-format=<format>   How to print errors: human, json, or sarif
+                              [default: human]
 ";
 
+// Exit codes
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_DIAGNOSTICS: i32 = 1;
+const EXIT_USAGE: i32 = 2;
 
-// Struct for storing command-line arguments
-#[derive(RustcDecodable, Debug)]
-struct Args {
-    arg_file: Option<String>,
-    flag_help: bool,
-}
+// The name stdin is given in diagnostics.
+const STDIN_NAME: &'static str = "<stdin>";
 
 
 fn main() {
     // Get command-line arguments
-    let args: Args = Docopt::new(USAGE).and_then(|d| d.decode()).unwrap_or_else(|e| e.exit());
-    
-    // Open file, if valid path
-    let mut f = if let Option::Some(s) = args.arg_file {
-        BufReader::new(File::open(&Path::new(&s[..])).unwrap())
+    let args = Docopt::new(USAGE)
+        .and_then(|d| d.version(Some(env!("CARGO_PKG_VERSION").to_string())).parse())
+        .unwrap_or_else(|e| {
+            if e.fatal() {
+                eprintln!("{}", e);
+                process::exit(EXIT_USAGE);
+            }
+            println!("{}", e);
+            process::exit(EXIT_SUCCESS);
+        });
+
+    if !args.get_str("--explain").is_empty() {
+        process::exit(explain(args.get_str("--explain")));
     }
-    else {
-        panic!("Invalid file path.")
+
+    let format = match ErrorFormat::from_name(args.get_str("--error-format")) {
+        Some(format) => format,
+        None => {
+            eprintln!("Unknown error format \"{}\".  Expected human, json, or sarif.", args.get_str("--error-format"));
+            process::exit(EXIT_USAGE);
+        }
     };
-    
-    // Read contents of file into a string
+
+    // Tokens and parse trees are printed to stdout, where they would be
+    // mixed up with machine-readable errors.
+    let prints_output = args.get_bool("lex") || args.get_bool("--dump-ast");
+    if prints_output && format != ErrorFormat::Human {
+        eprintln!("--error-format={} can't be used with \"rune lex\" or \"--dump-ast\".", args.get_str("--error-format"));
+        process::exit(EXIT_USAGE);
+    }
+
+    // Read the files.  Files that can't be read are reported, but the
+    // rest are still processed.
+    let mut source_map = SourceMap::new();
+    let mut diags = Vec::new();
+    let mut file_ids = Vec::new();
+    for path in args.get_vec("<file>") {
+        match read_file(path) {
+            Ok(text) => {
+                let name = if path == "-" { STDIN_NAME } else { path };
+                file_ids.push(source_map.add_file(name, text));
+            }
+            Err(e) => diags.push(Diagnostic::error(&format!("Couldn't read \"{}\": {}", path, e))),
+        }
+    }
+
+    // Run the command
+    if args.get_bool("lex") {
+        lex(&source_map, &file_ids, &mut diags);
+    }
+    else if args.get_bool("parse") {
+        parse(&source_map, &file_ids, false, args.get_bool("--dump-ast"), &mut diags);
+    }
+    else if args.get_bool("check") {
+        parse(&source_map, &file_ids, true, false, &mut diags);
+    }
+    else if args.get_bool("fmt") {
+        fmt(&source_map, &file_ids, args.get_bool("--check"), &mut diags);
+    }
+    else if args.get_bool("run") {
+        parse(&source_map, &file_ids, true, false, &mut diags);
+        if !has_errors(&diags) {
+            diags.push(Diagnostic::error("Running programs isn't supported yet."));
+        }
+    }
+
+    // Print errors
+    report(format, &diags[..], &source_map, args.get_bool("--color"));
+    if has_errors(&diags) {
+        process::exit(EXIT_DIAGNOSTICS);
+    }
+}
+
+
+// Prints the explanation of an error code, returning the exit code.
+fn explain(code: &str) -> i32 {
+    match codes::find(code) {
+        Some(c) => {
+            print!("{}", c.explanation);
+            EXIT_SUCCESS
+        }
+        None => {
+            eprintln!("Unknown error code \"{}\".", code);
+            EXIT_USAGE
+        }
+    }
+}
+
+
+// `rune lex`: prints the tokens of each file.
+fn lex(source_map: &SourceMap, file_ids: &[FileId], diags: &mut Vec<Diagnostic>) {
+    for &id in file_ids {
+        let file = source_map.file(id);
+        let (tokens, errors) = lex_file(file);
+        diags.extend(errors.iter().map(|e| e.to_diagnostic()));
+
+        if file_ids.len() > 1 {
+            println!("{}:", file.name());
+        }
+        for t in &tokens {
+            if t.kind == TokenKind::EOF {
+                println!("{}", t.kind);
+            }
+            else {
+                let pos = source_map.line_col(t.span);
+                println!("{}: [{}:{}]  \t{}", t.kind, pos.line + 1, pos.column + 1, t.text);
+            }
+        }
+    }
+}
+
+
+// `rune parse` and `rune check`: parses each file, optionally checking
+// that its literals decode, and printing its directives and parse tree.
+fn parse(source_map: &SourceMap, file_ids: &[FileId], check: bool, dump: bool, diags: &mut Vec<Diagnostic>) {
+    for &id in file_ids {
+        let file = source_map.file(id);
+        let (tokens, errors) = lex_file(file);
+        let tokens: Vec<_> = tokens.into_iter().collect();
+        diags.extend(errors.iter().map(|e| e.to_diagnostic()));
+        let lexed_cleanly = errors.iter().all(|e| e.kind.severity() != Severity::Error);
+
+        let (directives, directive_errors) = parse_directives(&tokens[..]);
+        let (tree, parse_errors) = parse_file(&tokens[..]);
+        diags.extend(directive_errors.iter().chain(parse_errors.iter()).map(|e| e.to_diagnostic()));
+
+        // Malformed literals have already been reported by the lexer
+        if check && lexed_cleanly {
+            diags.extend(check_literals(&tree).iter().map(|e| e.to_diagnostic()));
+        }
+        if !dump {
+            continue;
+        }
+
+        if file_ids.len() > 1 {
+            println!("{}:", file.name());
+        }
+        for d in directives {
+            let ss = d.source();
+            let pos = source_map.line_col(ss);
+            println!("Directive: [{}:{}]  \t{}", pos.line + 1, pos.column + 1, source_map.text(ss));
+        }
+        println!("{:#?}", tree);
+    }
+}
+
+
+// `rune fmt`: formats each file in place, or checks that it's formatted.
+// Stdin is formatted to stdout.
+fn fmt(source_map: &SourceMap, file_ids: &[FileId], check: bool, diags: &mut Vec<Diagnostic>) {
+    for &id in file_ids {
+        let file = source_map.file(id);
+        let formatted = match format_source(file.text()) {
+            Ok(formatted) => formatted,
+            Err(_) => {
+                // Report the errors with spans in this file
+                let (_, errors) = lex_file(file);
+                diags.extend(errors.iter().map(|e| e.to_diagnostic()));
+                diags.push(Diagnostic::error(&format!("Couldn't format \"{}\" because of the errors in it.", file.name())));
+                continue;
+            }
+        };
+
+        if file.name() == STDIN_NAME && !check {
+            print!("{}", formatted);
+        }
+        else if formatted != file.text() {
+            if check {
+                diags.push(Diagnostic::error(&format!("\"{}\" isn't formatted.", file.name()))
+                    .with_help("Run \"rune fmt\" to format it."));
+            }
+            else if let Err(e) = File::create(file.name()).and_then(|mut f| f.write_all(formatted.as_bytes())) {
+                diags.push(Diagnostic::error(&format!("Couldn't write \"{}\": {}", file.name(), e)));
+            }
+        }
+    }
+}
+
+
+// Reads a source file, or stdin for "-".
+fn read_file(path: &str) -> io::Result<String> {
     let mut text = String::new();
-    match f.read_to_string(&mut text) {
-        Ok(_) => {},
-        Err(_) => panic!("Failed to read file.")
+    let result = if path == "-" {
+        io::stdin().read_to_string(&mut text)
     }
+    else {
+        File::open(path).and_then(|mut f| f.read_to_string(&mut text))
+    };
+    result.map(|_| text)
+}
 
-    // Lex the string
-    let tokens = lex_str(&text[..]);
-    
-    // Print tokens
-    for t in tokens {
-        println!("{}", t);
+
+fn has_errors(diags: &[Diagnostic]) -> bool {
+    diags.iter().any(|d| d.severity == Severity::Error)
+}
+
+
+// Prints diagnostics.  Human-readable ones go to stderr, while the
+// machine-readable formats go to stdout, even when there are none.
+fn report(format: ErrorFormat, diags: &[Diagnostic], source_map: &SourceMap, color: bool) {
+    let rendered = diagnostics::render(format, diags, source_map, color);
+    if format == ErrorFormat::Human {
+        eprint!("{}", rendered);
+    }
+    else {
+        print!("{}", rendered);
     }
 }
@@ -0,0 +1,165 @@
+use super::super::source_map::Span;
+use super::super::token::{Token, TokenKind};
+use super::{ParseResult, ParseError, Parseable, expect_token, skip_newlines, span_between};
+use super::super::diagnostics::codes;
+
+use super::declaration::Declaration;
+use super::expression::Expression;
+use super::node_id::NodeId;
+use super::precedence::{self, PrecedenceTable};
+
+/// A parenthesized block of code, e.g.
+///
+/// ```text
+/// (
+///     val c = a + b
+///     return c
+/// )
+/// ```
+///
+/// The statements of a block are separated by newlines.
+#[derive(Clone, Debug)]
+pub struct Block {
+    pub id: NodeId,
+    pub statements: Vec<Statement>,
+    pub source: Span,
+}
+
+
+/// A statement in a block: either a declaration or an expression.
+#[derive(Clone, Debug)]
+pub enum Statement {
+    Declaration(Declaration),
+    Expression(Expression),
+}
+
+impl Statement {
+    pub fn id(&self) -> NodeId {
+        match *self {
+            Statement::Declaration(ref d) => d.id(),
+            Statement::Expression(ref e) => e.id,
+        }
+    }
+
+
+    pub fn source(&self) -> Span {
+        match *self {
+            Statement::Declaration(ref d) => d.source(),
+            Statement::Expression(ref e) => e.source,
+        }
+    }
+}
+
+
+//=============================
+impl<'s, 't> Parseable<'s, 't> for Block {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        Block::parse_with(tokens, &precedence::BUILTIN)
+    }
+
+
+    fn parse_with(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> ParseResult<'s, 't, Self> {
+        let (_, mut rem) = expect_token(tokens, TokenKind::LParen, "\"(\"")?;
+        let mut statements = Vec::new();
+        loop {
+            rem = skip_newlines(rem);
+            if rem.get(0).map(|t| t.kind) == Some(TokenKind::RParen) {
+                break;
+            }
+            let (statement, r) = Statement::parse_with(rem, precedence)?;
+            statements.push(statement);
+            rem = r;
+
+            // Each statement ends the line, or the block
+            match rem.get(0).map(|t| t.kind) {
+                Some(TokenKind::NewLine) | Some(TokenKind::RParen) => {}
+                _ => {
+                    return Err(ParseError {
+                        code: codes::UNEXPECTED_TOKEN,
+                        message: "Expected newline or \")\".".to_string(),
+                        source: rem.get(0).map(|t| t.span),
+                        help: None,
+                    });
+                }
+            }
+        }
+        let rem = &rem[1..];
+        return Ok((
+            Block {
+                id: NodeId::next(),
+                statements: statements,
+                source: span_between(tokens, rem),
+            },
+            rem,
+        ));
+    }
+}
+
+
+impl<'s, 't> Parseable<'s, 't> for Statement {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        Statement::parse_with(tokens, &precedence::BUILTIN)
+    }
+
+
+    fn parse_with(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> ParseResult<'s, 't, Self> {
+        match tokens.get(0).map(|t| t.kind) {
+            Some(TokenKind::KEY_Type) |
+            Some(TokenKind::KEY_Const) |
+            Some(TokenKind::KEY_Val) |
+            Some(TokenKind::KEY_Var) => {
+                let (decl, rem) = Declaration::parse_with(tokens, precedence)?;
+                return Ok((Statement::Declaration(decl), rem));
+            }
+            _ => {
+                let (expr, rem) = Expression::parse_with(tokens, precedence)?;
+                return Ok((Statement::Expression(expr), rem));
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::expression::ExpressionKind;
+    use lexer::lex_str;
+
+    fn parse(text: &str) -> Result<Block, String> {
+        let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+        match Block::parse(&tokens[..]) {
+            Ok((block, rem)) => {
+                assert_eq!(rem[0].kind, TokenKind::EOF);
+                Ok(block)
+            }
+            Err(e) => Err(e.message),
+        }
+    }
+
+    #[test]
+    fn blocks() {
+        assert_eq!(parse("()").unwrap().statements.len(), 0);
+        assert_eq!(parse("(a + b)").unwrap().statements.len(), 1);
+
+        let block = parse("(\n\tval c = a + b\n\n\tc\n)").unwrap();
+        assert_eq!(block.statements.len(), 2);
+        match block.statements[0] {
+            Statement::Declaration(Declaration::Val(_)) => {}
+            ref s => panic!("Expected a val declaration, got {:?}", s),
+        }
+        match block.statements[1] {
+            Statement::Expression(Expression { kind: ExpressionKind::Identifier(_), .. }) => {}
+            ref s => panic!("Expected an identifier, got {:?}", s),
+        }
+        assert_eq!(block.source.range(), 0..22);
+        assert_eq!(block.statements[1].source().range(), 19..20);
+        assert!(block.statements[0].id() != block.statements[1].id());
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(parse("(a, b)").unwrap_err(), "Expected newline or \")\".");
+        assert_eq!(parse("(a\n").unwrap_err(), "Expected expression.");
+    }
+}
@@ -1,6 +1,361 @@
+use super::super::source_map::Span;
+use super::super::symbol::Symbol;
+use super::super::token::{Token, TokenKind};
+use super::{ParseResult, ParseError, Parseable, expect_token, skip_newlines, span_between};
+use super::super::diagnostics::codes;
+
 use super::namespace::Namespace;
+use super::expression::Expression;
+use super::type_expr::TypeExpr;
+use super::node_id::NodeId;
+use super::precedence::{self, PrecedenceTable};
+
+#[derive(Clone, Debug)]
+pub enum Declaration {
+    Namespace(Namespace),
+    Type(TypeDecl),
+    Const(ConstDecl),
+    Val(ValDecl),
+    Var(VarDecl),
+}
+
+impl Declaration {
+    pub fn id(&self) -> NodeId {
+        match *self {
+            Declaration::Namespace(ref d) => d.id,
+            Declaration::Type(ref d) => d.id,
+            Declaration::Const(ref d) => d.id,
+            Declaration::Val(ref d) => d.id,
+            Declaration::Var(ref d) => d.id,
+        }
+    }
+
+
+    pub fn source(&self) -> Span {
+        match *self {
+            Declaration::Namespace(ref d) => d.source,
+            Declaration::Type(ref d) => d.source,
+            Declaration::Const(ref d) => d.source,
+            Declaration::Val(ref d) => d.source,
+            Declaration::Var(ref d) => d.source,
+        }
+    }
+
+
+    /// The name being declared, if the declaration has one.
+    pub fn name(&self) -> Option<Symbol> {
+        match *self {
+            Declaration::Namespace(_) => None,
+            Declaration::Type(ref d) => Some(d.name),
+            Declaration::Const(ref d) => Some(d.name),
+            Declaration::Val(ref d) => Some(d.name),
+            Declaration::Var(ref d) => Some(d.name),
+        }
+    }
+}
+
+
+/// A type declaration, e.g. `type Meters: f32`.  The type is required.
+#[derive(Clone, Debug)]
+pub struct TypeDecl {
+    pub id: NodeId,
+    pub name: Symbol,
+    pub ty: TypeExpr,
+    pub source: Span,
+}
+
+
+/// A compile-time constant declaration, e.g. `const a: i32 = 42`.  The
+/// initializer is required.
+#[derive(Clone, Debug)]
+pub struct ConstDecl {
+    pub id: NodeId,
+    pub name: Symbol,
+    pub ty: Option<TypeExpr>,
+    pub init: Expression,
+    pub source: Span,
+}
+
+
+/// An immutable variable declaration, e.g. `val a: i32 = 42`.  The
+/// initializer is required.
+#[derive(Clone, Debug)]
+pub struct ValDecl {
+    pub id: NodeId,
+    pub name: Symbol,
+    pub ty: Option<TypeExpr>,
+    pub init: Expression,
+    pub source: Span,
+}
+
+
+/// A mutable variable declaration, e.g. `var a: i32 = 42`.  Both the type
+/// and the initializer are optional.
+#[derive(Clone, Debug)]
+pub struct VarDecl {
+    pub id: NodeId,
+    pub name: Symbol,
+    pub ty: Option<TypeExpr>,
+    pub init: Option<Expression>,
+    pub source: Span,
+}
+
+
+//=============================
+impl<'s, 't> Parseable<'s, 't> for Declaration {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        Declaration::parse_with(tokens, &precedence::BUILTIN)
+    }
+
+
+    fn parse_with(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> ParseResult<'s, 't, Self> {
+        match tokens.get(0).map(|t| t.kind) {
+            Some(TokenKind::KEY_Namespace) => {
+                let (ns, rem) = Namespace::parse_with(tokens, precedence)?;
+                return Ok((Declaration::Namespace(ns), rem));
+            }
+            Some(TokenKind::KEY_Type) => {
+                let (d, rem) = TypeDecl::parse(tokens)?;
+                return Ok((Declaration::Type(d), rem));
+            }
+            Some(TokenKind::KEY_Const) => {
+                let (d, rem) = ConstDecl::parse_with(tokens, precedence)?;
+                return Ok((Declaration::Const(d), rem));
+            }
+            Some(TokenKind::KEY_Val) => {
+                let (d, rem) = ValDecl::parse_with(tokens, precedence)?;
+                return Ok((Declaration::Val(d), rem));
+            }
+            Some(TokenKind::KEY_Var) => {
+                let (d, rem) = VarDecl::parse_with(tokens, precedence)?;
+                return Ok((Declaration::Var(d), rem));
+            }
+            _ => {
+                return Err(ParseError {
+                    code: codes::UNEXPECTED_TOKEN,
+                    message: "Expected declaration.".to_string(),
+                    source: tokens.get(0).map(|t| t.span),
+                    help: None,
+                });
+            }
+        }
+    }
+}
+
+
+impl<'s, 't> Parseable<'s, 't> for TypeDecl {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        let (_, rem) = expect_token(tokens, TokenKind::KEY_Type, "\"type\"")?;
+        let (name, rem) = expect_token(rem, TokenKind::Identifier, "type name")?;
+        if rem.get(0).map(|t| t.kind) != Some(TokenKind::Colon) {
+            return Err(ParseError {
+                code: codes::MISSING_TYPE,
+                message: "Type declarations need a type, e.g. \"type Meters: f32\".".to_string(),
+                source: Some(span_between(tokens, rem)),
+                help: None,
+            });
+        }
+        let (ty, rem) = TypeExpr::parse(&rem[1..])?;
+        return Ok((
+            TypeDecl {
+                id: NodeId::next(),
+                name: name.symbol().unwrap(),
+                ty: ty,
+                source: span_between(tokens, rem),
+            },
+            rem,
+        ));
+    }
+}
+
+
+impl<'s, 't> Parseable<'s, 't> for ConstDecl {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        ConstDecl::parse_with(tokens, &precedence::BUILTIN)
+    }
+
+
+    fn parse_with(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> ParseResult<'s, 't, Self> {
+        let ((name, ty, init), rem) = parse_thing_decl(tokens, precedence, TokenKind::KEY_Const)?;
+        return Ok((
+            ConstDecl {
+                id: NodeId::next(),
+                name: name,
+                ty: ty,
+                init: require_initializer(init, "const", tokens, rem)?,
+                source: span_between(tokens, rem),
+            },
+            rem,
+        ));
+    }
+}
+
+
+impl<'s, 't> Parseable<'s, 't> for ValDecl {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        ValDecl::parse_with(tokens, &precedence::BUILTIN)
+    }
+
+
+    fn parse_with(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> ParseResult<'s, 't, Self> {
+        let ((name, ty, init), rem) = parse_thing_decl(tokens, precedence, TokenKind::KEY_Val)?;
+        return Ok((
+            ValDecl {
+                id: NodeId::next(),
+                name: name,
+                ty: ty,
+                init: require_initializer(init, "val", tokens, rem)?,
+                source: span_between(tokens, rem),
+            },
+            rem,
+        ));
+    }
+}
+
+
+impl<'s, 't> Parseable<'s, 't> for VarDecl {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        VarDecl::parse_with(tokens, &precedence::BUILTIN)
+    }
+
+
+    fn parse_with(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> ParseResult<'s, 't, Self> {
+        let ((name, ty, init), rem) = parse_thing_decl(tokens, precedence, TokenKind::KEY_Var)?;
+        return Ok((
+            VarDecl {
+                id: NodeId::next(),
+                name: name,
+                ty: ty,
+                init: init,
+                source: span_between(tokens, rem),
+            },
+            rem,
+        ));
+    }
+}
+
+
+// Parses a const, val or var declaration: the keyword, the name, and an
+// optional type ascription and initializer.
+fn parse_thing_decl<'s, 't>(tokens: &'s [Token<'t>], precedence: &PrecedenceTable, keyword: TokenKind)
+    -> ParseResult<'s, 't, (Symbol, Option<TypeExpr>, Option<Expression>)>
+{
+    let keyword_text = format!("\"{}\"", keyword.canonical_text().unwrap());
+    let (_, rem) = expect_token(tokens, keyword, &keyword_text)?;
+    let (name, rem) = expect_token(rem, TokenKind::Identifier, &format!("name after {}", keyword_text))?;
+
+    // Type ascription
+    let (ty, rem) = if rem.get(0).map(|t| t.kind) == Some(TokenKind::Colon) {
+        let (ty, rem) = TypeExpr::parse(&rem[1..])?;
+        (Some(ty), rem)
+    }
+    else {
+        (None, rem)
+    };
+
+    // Initializer
+    let (init, rem) = match rem.get(0) {
+        Some(t) if t.kind == TokenKind::Operator && t.text == "=" => {
+            let (init, rem) = Expression::parse_with(skip_newlines(&rem[1..]), precedence)?;
+            (Some(init), rem)
+        }
+        _ => (None, rem),
+    };
+
+    return Ok(((name.symbol().unwrap(), ty, init), rem));
+}
+
+
+// Fails if a declaration that needs an initializer doesn't have one.
+fn require_initializer<'s, 't>(init: Option<Expression>, keyword: &str, tokens: &'s [Token<'t>], rem: &'s [Token<'t>])
+    -> Result<Expression, ParseError>
+{
+    match init {
+        Some(init) => Ok(init),
+        None => Err(ParseError {
+            code: codes::MISSING_INITIALIZER,
+            message: format!("\"{0}\" declarations need an initializer, e.g. \"{0} a = 42\".", keyword),
+            source: Some(span_between(tokens, rem)),
+            help: None,
+        }),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::lex_str;
+
+    fn parse(text: &str) -> Result<Declaration, ParseError> {
+        let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+        match Declaration::parse(&tokens[..]) {
+            Ok((decl, rem)) => {
+                assert_eq!(rem[0].kind, TokenKind::EOF);
+                Ok(decl)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    #[test]
+    fn type_decl() {
+        match parse("type Meters: f32").unwrap() {
+            Declaration::Type(d) => {
+                assert_eq!(d.name, Symbol::intern("Meters"));
+                assert_eq!(d.source.range(), 0..16);
+            }
+            d => panic!("Expected a type declaration, got {:?}", d),
+        }
+
+        let e = parse("type Meters").unwrap_err();
+        assert_eq!(e.code, codes::MISSING_TYPE);
+        assert_eq!(e.source.unwrap().range(), 0..11);
+    }
+
+    #[test]
+    fn thing_decls() {
+        match parse("const c: i32 = 456").unwrap() {
+            Declaration::Const(d) => {
+                assert_eq!(d.name, Symbol::intern("c"));
+                assert!(d.ty.is_some());
+                assert_eq!(d.init.source.range(), 15..18);
+            }
+            d => panic!("Expected a const declaration, got {:?}", d),
+        }
+        match parse("val a = 1").unwrap() {
+            Declaration::Val(d) => assert!(d.ty.is_none()),
+            d => panic!("Expected a val declaration, got {:?}", d),
+        }
+        match parse("var b").unwrap() {
+            Declaration::Var(d) => {
+                assert!(d.ty.is_none());
+                assert!(d.init.is_none());
+            }
+            d => panic!("Expected a var declaration, got {:?}", d),
+        }
+        match parse("var b: @u8").unwrap() {
+            Declaration::Var(d) => assert!(d.ty.is_some() && d.init.is_none()),
+            d => panic!("Expected a var declaration, got {:?}", d),
+        }
+    }
+
+    #[test]
+    fn missing_initializer() {
+        let e = parse("const c: i32").unwrap_err();
+        assert_eq!(e.code, codes::MISSING_INITIALIZER);
+        assert_eq!(e.message, "\"const\" declarations need an initializer, e.g. \"const a = 42\".");
+        assert_eq!(e.source.unwrap().range(), 0..12);
+
+        let e = parse("val a").unwrap_err();
+        assert_eq!(e.code, codes::MISSING_INITIALIZER);
+    }
 
-#[derive(Eq, PartialEq, Copy, Clone, Debug)]
-pub enum Declaration<'a> {
-    Namespace(Namespace<'a>),
-}
\ No newline at end of file
+    #[test]
+    fn invalid() {
+        assert_eq!(parse("val = 3").unwrap_err().message, "Expected name after \"val\".");
+        assert_eq!(parse("var a: = 3").unwrap_err().message, "Expected type.");
+        assert_eq!(parse("val a =").unwrap_err().message, "Expected expression.");
+        assert_eq!(parse("42").unwrap_err().message, "Expected declaration.");
+    }
+}
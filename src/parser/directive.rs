@@ -0,0 +1,203 @@
+use super::super::source_map::Span;
+use super::super::token::{Token, TokenKind};
+use super::super::symbol::Symbol;
+use lexer::normalize_identifier;
+use super::{ParseResult, ParseError, Parseable};
+use super::super::diagnostics::codes;
+
+/// A compiler directive, e.g. `%import std.io`.
+///
+/// Directives take up the rest of their line.  `%import` has its own
+/// syntax, while any other directive is kept in a generic form with the
+/// tokens of its arguments.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Directive<'s, 't> {
+    Import(Import<'s, 't>),
+    Generic(GenericDirective<'s, 't>),
+}
+
+impl<'s, 't> Directive<'s, 't> {
+    /// The name of the directive, without the "%".
+    pub fn name(&self) -> Symbol {
+        match *self {
+            Directive::Import(_) => Symbol::intern("import"),
+            Directive::Generic(d) => d.name,
+        }
+    }
+
+    pub fn source(&self) -> Span {
+        match *self {
+            Directive::Import(d) => d.source,
+            Directive::Generic(d) => d.source,
+        }
+    }
+}
+
+
+/// An `%import` directive, which imports a module by its path, e.g.
+/// `std.io`.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct Import<'s, 't> {
+    /// The tokens of the path, including the periods.
+    pub path: &'s [Token<'t>],
+    pub source: Span,
+}
+
+impl<'s, 't> Import<'s, 't> {
+    /// The names that make up the module path.
+    pub fn path_parts(&self) -> Vec<Symbol> {
+        self.path.iter().filter_map(|t| if t.kind == TokenKind::Identifier { t.symbol() } else { None }).collect()
+    }
+
+
+    /// The span of the module path.
+    pub fn path_span(&self) -> Span {
+        self.path[0].span.merge(self.path[self.path.len() - 1].span)
+    }
+}
+
+
+/// Any directive other than the ones with their own syntax.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct GenericDirective<'s, 't> {
+    pub name: Symbol,
+    pub args: &'s [Token<'t>],
+    pub source: Span,
+}
+
+
+//=============================
+impl<'s, 't> Parseable<'s, 't> for Directive<'s, 't> {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        let name = if let Some(&t @ Token { kind: TokenKind::Directive, .. }) = tokens.get(0) {
+            t
+        }
+        else {
+            let ss = tokens.get(0).map(|t| t.span);
+            return Err(ParseError {
+                code: codes::UNEXPECTED_TOKEN,
+                message: "Expected compiler directive.".to_string(),
+                source: ss,
+                help: None,
+            });
+        };
+
+        // The directive's arguments go to the end of the line
+        let mut end = 1;
+        loop {
+            match tokens.get(end).map(|t| t.kind) {
+                None | Some(TokenKind::NewLine) | Some(TokenKind::EOF) => break,
+                _ => end += 1,
+            }
+        }
+        let args = &tokens[1..end];
+        let source = match args.last() {
+            Some(t) => name.span.merge(t.span),
+            None => name.span,
+        };
+
+        // Import
+        if name.text == "%import" {
+            if !is_path(args) {
+                return Err(ParseError {
+                    code: codes::INVALID_IMPORT_PATH,
+                    message: "Expected module path after \"%import\", e.g. \"%import std.io\".".to_string(),
+                    source: Some(source),
+                    help: None,
+                });
+            }
+            return Ok((
+                Directive::Import(Import {
+                    path: args,
+                    source: source,
+                }),
+                &tokens[end..],
+            ));
+        }
+
+        // Everything else
+        return Ok((
+            Directive::Generic(GenericDirective {
+                name: Symbol::intern(&normalize_identifier(&name.text[1..])),
+                args: args,
+                source: source,
+            }),
+            &tokens[end..],
+        ));
+    }
+}
+
+
+// Checks that the whole token slice is a module path, i.e. identifiers
+// separated by periods.
+fn is_path(tokens: &[Token]) -> bool {
+    for (i, token) in tokens.iter().enumerate() {
+        match (i % 2, token.kind) {
+            (0, TokenKind::Identifier) | (1, TokenKind::Period) => {},
+            _ => return false,
+        }
+    }
+
+    // Can't be empty or end with a period
+    return tokens.len() % 2 == 1;
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Parseable;
+    use lexer::lex_str;
+    use source_map::FileId;
+
+    #[test]
+    fn import() {
+        let text = "%import std.io\nval a";
+        let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+        let (directive, rem) = Directive::parse(&tokens[..]).unwrap();
+        if let Directive::Import(import) = directive {
+            assert_eq!(&text[import.path_span().range()], "std.io");
+            assert_eq!(import.path_parts(), vec![Symbol::intern("std"), Symbol::intern("io")]);
+            assert_eq!(&text[import.source.range()], "%import std.io");
+        }
+        else {
+            panic!("Expected an import, got {:?}", directive);
+        }
+        assert_eq!(directive.name(), Symbol::intern("import"));
+        assert_eq!(rem[0].kind, TokenKind::NewLine);
+    }
+
+    #[test]
+    fn import_errors() {
+        for text in &["%import", "%import std.", "%import .io", "%import std io", "%import \"std\""] {
+            let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+            assert!(Directive::parse(&tokens[..]).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn generic() {
+        let text = "%warn unused 3\n";
+        let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+        let (directive, rem) = Directive::parse(&tokens[..]).unwrap();
+        if let Directive::Generic(d) = directive {
+            assert_eq!(d.name, Symbol::intern("warn"));
+            assert_eq!(d.args.len(), 2);
+            assert_eq!(&text[d.source.range()], "%warn unused 3");
+        }
+        else {
+            panic!("Expected a generic directive, got {:?}", directive);
+        }
+        assert_eq!(rem.len(), 2);
+    }
+
+    #[test]
+    fn no_args() {
+        let tokens: Vec<_> = lex_str("%foo").0.into_iter().collect();
+        let (directive, rem) = Directive::parse(&tokens[..]).unwrap();
+        assert_eq!(directive.name(), Symbol::intern("foo"));
+        assert_eq!(directive.source(), Span::new(FileId(0), 0, 4));
+        assert_eq!(rem.len(), 1);
+        assert_eq!(rem[0].kind, TokenKind::EOF);
+    }
+}
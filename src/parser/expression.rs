@@ -1,41 +1,749 @@
-use std::collections::VecDeque;
-use super::super::source_span::SourceSpan;
-use super::super::token::Token;
-use super::{ParseResult, ParseError, Parseable};
+use super::super::source_map::Span;
+use super::super::symbol::Symbol;
+use super::super::token::{Token, TokenKind};
+use super::{ParseResult, ParseError, Parseable, expect_token, parse_list, skip_newlines, span_between};
+use super::super::diagnostics::codes;
 
 use super::lit_integer::LitInteger;
 use super::lit_real::LitReal;
+use super::lit_string::LitString;
+use super::lit_codepoint::LitCodepoint;
+use super::block::{Block, Statement};
+use super::fn_literal::FnLiteral;
+use super::node_id::NodeId;
+use super::precedence::{self, Associativity, Precedence, PrecedenceTable};
 
+#[derive(Clone, Debug)]
+pub struct Expression {
+    pub id: NodeId,
+    pub kind: ExpressionKind,
+    pub source: Span,
+}
+
+
+#[derive(Clone, Debug)]
+pub enum ExpressionKind {
+    LitInteger(LitInteger),
+    LitReal(LitReal),
+    LitString(LitString),
+    LitCodepoint(LitCodepoint),
+
+    /// A name, e.g. `foo`.
+    Identifier(Symbol),
+
+    /// A parenthesized expression, e.g. `(a + b)`.
+    Group(Box<Expression>),
+
+    /// A parenthesized block of code, e.g. `(val c = a + b)`.
+    Block(Block),
+
+    /// A function literal, e.g. `fn [x: i32] -> i32 (x)`.
+    FnLiteral(FnLiteral),
+
+    /// A function call, e.g. `foo[a, b]`, however it was written.
+    Call { callee: Box<Expression>, args: Vec<Expression>, syntax: CallSyntax },
+
+    /// A prefix operator applied to an operand, e.g. `-a` or `$ptr`.
+    Prefix { op: Operator, operand: Box<Expression> },
+
+    /// A postfix operator applied to an operand, e.g. `a++`.
+    Postfix { op: Operator, operand: Box<Expression> },
+
+    /// An infix operator applied to two operands, e.g. `a + b`.
+    Infix { op: Operator, left: Box<Expression>, right: Box<Expression> },
+
+    /// An assignment, e.g. `a = b` or `a += b`.
+    Assign { op: Operator, target: Box<Expression>, value: Box<Expression> },
+
+    /// A return, with an optional value.
+    Return(Option<Box<Expression>>),
+}
+
+
+/// How a function call was written.  Call sugar is parsed into the same
+/// call as the standard syntax, with the arguments in order.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum CallSyntax {
+    /// `foo[1, 3]`
+    Standard,
+
+    /// `foo 5`, which is `foo[5]`.
+    Prefix,
+
+    /// `1 foo 3`, which is `foo[1, 3]`.
+    Infix,
+
+    /// `1.foo[3]`, which is `foo[1, 3]`.
+    Method,
+}
+
+
+/// An operator, as used in a prefix or infix expression.
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
-pub enum Expression<'a> {
-    LitInteger(LitInteger<'a>),
-    LitReal(LitReal<'a>),
+pub struct Operator {
+    pub name: Symbol,
+    pub source: Span,
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, source: Span) -> Expression {
+        Expression {
+            id: NodeId::next(),
+            kind: kind,
+            source: source,
+        }
+    }
 }
 
+impl Operator {
+    fn from_token(token: &Token) -> Operator {
+        Operator {
+            name: Symbol::intern(token.text),
+            source: token.span,
+        }
+    }
+}
+
+
 //=============================
-impl<'a> Parseable<'a> for Expression<'a> {
-    fn parse(tokens: &'a [Token<'a>]) -> ParseResult<'a, Self> {    
-        // Integer literal
-        if let Ok((lit_int, rem_tokens)) = LitInteger::parse(tokens) {
-            return Ok((
-                Expression::LitInteger(lit_int),
-                rem_tokens,
-            ));
+impl<'s, 't> Parseable<'s, 't> for Expression {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        Expression::parse_with(tokens, &precedence::BUILTIN)
+    }
+
+
+    fn parse_with(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> ParseResult<'s, 't, Self> {
+        parse_binary(tokens, precedence, 0)
+    }
+}
+
+
+/// The infix operators that assign to their left operand.
+pub const ASSIGNMENT_OPERATORS: &'static [&'static str] = &[
+    "=", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "<<=", ">>=",
+];
+
+
+// Parses operands separated by infix operators, Pratt style.  Only
+// operators with a precedence level of at least `min_level` are taken:
+// an operator with a lower level is left for a caller further up, which
+// takes everything parsed so far as its left operand.  A newline may
+// follow an infix operator, but not precede it.
+fn parse_binary<'s, 't>(tokens: &'s [Token<'t>], precedence: &PrecedenceTable, min_level: u32) -> ParseResult<'s, 't, Expression> {
+    let (mut left, mut rem) = parse_call_sugar(tokens, precedence)?;
+    loop {
+        let op = match rem.get(0) {
+            Some(t) if t.kind == TokenKind::Operator => t,
+            _ => break,
+        };
+        let Precedence { level, associativity } = precedence.get(op.text);
+        if level < min_level {
+            break;
+        }
+        let next_min = match associativity {
+            Associativity::Left => level + 1,
+            Associativity::Right => level,
+        };
+        let (right, r) = parse_binary(skip_newlines(&rem[1..]), precedence, next_min)?;
+        rem = r;
+        let op = Operator::from_token(op);
+        let kind = if ASSIGNMENT_OPERATORS.contains(&op.name.as_str()) {
+            ExpressionKind::Assign {
+                op: op,
+                target: Box::new(left),
+                value: Box::new(right),
+            }
+        }
+        else {
+            ExpressionKind::Infix {
+                op: op,
+                left: Box::new(left),
+                right: Box::new(right),
+            }
+        };
+        left = Expression::new(kind, span_between(tokens, rem));
+    }
+    return Ok((left, rem));
+}
+
+
+// Parses a run of operands without operators between them, which is
+// function call sugar: two operands are a prefix call, `foo 5` being
+// `foo[5]`, and three are an infix call, `1 foo 3` being `foo[1, 3]`.  A
+// run of one operand is just that operand.  Call sugar binds tighter than
+// infix operators, so `foo 5 + 1` is `foo[5] + 1`.
+fn parse_call_sugar<'s, 't>(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> ParseResult<'s, 't, Expression> {
+    let (first, mut rem) = parse_unary(tokens, precedence)?;
+    let mut operands = vec![first];
+    while starts_operand(operands[operands.len() - 1].source, rem) {
+        let (operand, r) = parse_unary(rem, precedence)?;
+        operands.push(operand);
+        rem = r;
+    }
+    let source = span_between(tokens, rem);
+
+    let (callee, args, syntax) = match operands.len() {
+        1 => return Ok((operands.pop().unwrap(), rem)),
+        2 => {
+            let arg = operands.pop().unwrap();
+            (operands.pop().unwrap(), vec![arg], CallSyntax::Prefix)
+        }
+        3 => {
+            let right = operands.pop().unwrap();
+            let callee = operands.pop().unwrap();
+            (callee, vec![operands.pop().unwrap(), right], CallSyntax::Infix)
+        }
+        n => {
+            // Suggest the groupings at either end, e.g. `double[add 1 2]`
+            // and `double add[1, 2]`
+            let texts: Vec<_> = operands.iter().map(|o| text_of(tokens, o.source)).collect();
+            let help = format!("Use brackets to show which calls are meant, e.g. \"{}[{}]\" or \"{} {}[{}, {}]\".",
+                texts[0], texts[1..].join(" "), texts[..(n - 3)].join(" "), texts[n - 3], texts[n - 2], texts[n - 1]);
+            return Err(ParseError {
+                code: codes::AMBIGUOUS_CALL,
+                message: format!("Ambiguous function call sugar: {} operands in a row can be grouped into calls more than one way.", n),
+                source: Some(source),
+                help: Some(help),
+            });
+        }
+    };
+
+    // Call sugar only calls functions by name
+    if let ExpressionKind::Identifier(_) = callee.kind {}
+    else {
+        let example = if syntax == CallSyntax::Prefix { "foo 5" } else { "1 foo 3" };
+        return Err(ParseError {
+            code: codes::CALL_WITHOUT_NAME,
+            message: format!("Expected a function name to call, as in \"{}\".  Is an operator missing?", example),
+            source: Some(callee.source),
+            help: call_without_name_help(tokens, &callee, &args, syntax),
+        });
+    }
+
+    let kind = ExpressionKind::Call {
+        callee: Box::new(callee),
+        args: args,
+        syntax: syntax,
+    };
+    return Ok((Expression::new(kind, source), rem));
+}
+
+
+// Help for call sugar whose callee is itself a call, e.g. `foo[1] 2`,
+// which was likely meant to pass more arguments.
+fn call_without_name_help(tokens: &[Token], callee: &Expression, args: &[Expression], syntax: CallSyntax) -> Option<String> {
+    let (inner, inner_args) = match callee.kind {
+        ExpressionKind::Call { callee: ref inner, args: ref inner_args, syntax: CallSyntax::Standard } => (inner, inner_args),
+        _ => return None,
+    };
+    let args: Vec<_> = args.iter().map(|a| text_of(tokens, a.source)).collect();
+    let calls_result = format!("{}[{}]", text_of(tokens, callee.source), args.join(", "));
+    if syntax == CallSyntax::Prefix {
+        let all_args: Vec<_> = inner_args.iter().map(|a| text_of(tokens, a.source)).chain(args).collect();
+        return Some(format!("To pass all of the arguments, write \"{}[{}]\", or to call the result, \"{}\".",
+            text_of(tokens, inner.source), all_args.join(", "), calls_result));
+    }
+    return Some(format!("To call the result, write \"{}\".", calls_result));
+}
+
+
+// The text of the tokens within `span`, with a space wherever there's
+// whitespace between them, for quoting code in messages.
+fn text_of(tokens: &[Token], span: Span) -> String {
+    let mut text = String::new();
+    let mut end = None;
+    for t in tokens.iter().filter(|t| t.span.lo >= span.lo && t.span.hi <= span.hi) {
+        if t.kind == TokenKind::NewLine {
+            continue;
+        }
+        if end.is_some() && end != Some(t.span.lo) {
+            text.push(' ');
+        }
+        text.push_str(t.text);
+        end = Some(t.span.hi);
+    }
+    return text;
+}
+
+
+// Parses an operand with any prefix operators before it.  Prefix
+// operators bind looser than postfix operators and calls, so `-a[1]` is
+// `-(a[1])`.
+fn parse_unary<'s, 't>(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> ParseResult<'s, 't, Expression> {
+    match tokens.get(0) {
+        Some(op) if op.kind == TokenKind::Operator || op.kind == TokenKind::At || op.kind == TokenKind::Dollar => {
+            let (operand, rem) = parse_unary(&tokens[1..], precedence)?;
+            let kind = ExpressionKind::Prefix {
+                op: Operator::from_token(op),
+                operand: Box::new(operand),
+            };
+            return Ok((Expression::new(kind, span_between(tokens, rem)), rem));
+        }
+        _ => parse_postfix(tokens, precedence),
+    }
+}
+
+
+// Parses a primary expression followed by any number of calls, method
+// calls and postfix operators.
+fn parse_postfix<'s, 't>(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> ParseResult<'s, 't, Expression> {
+    let (mut expr, mut rem) = parse_primary(tokens, precedence)?;
+    loop {
+        let kind = match rem.get(0) {
+            Some(t) if t.kind == TokenKind::LSquare => {
+                let (args, r) = parse_list(&rem[1..], TokenKind::RSquare, |t| Expression::parse_with(t, precedence))?;
+                rem = r;
+                ExpressionKind::Call {
+                    callee: Box::new(expr),
+                    args: args,
+                    syntax: CallSyntax::Standard,
+                }
+            }
+            Some(t) if t.kind == TokenKind::Period => {
+                let (name, r) = expect_token(&rem[1..], TokenKind::Identifier, "method name after \".\"")?;
+                let (_, r) = expect_token(r, TokenKind::LSquare, "\"[\" after method name")?;
+                let (mut args, r) = parse_list(r, TokenKind::RSquare, |t| Expression::parse_with(t, precedence))?;
+                rem = r;
+                args.insert(0, expr);
+                ExpressionKind::Call {
+                    callee: Box::new(Expression::new(ExpressionKind::Identifier(name.symbol().unwrap()), name.span)),
+                    args: args,
+                    syntax: CallSyntax::Method,
+                }
+            }
+            Some(t) if is_postfix(expr.source, t, rem.get(1)) => {
+                rem = &rem[1..];
+                ExpressionKind::Postfix {
+                    op: Operator::from_token(t),
+                    operand: Box::new(expr),
+                }
+            }
+            _ => break,
+        };
+        expr = Expression::new(kind, span_between(tokens, rem));
+    }
+    return Ok((expr, rem));
+}
+
+
+// Whether the tokens after an operand start another operand, e.g. the
+// `5` of `foo 5`, rather than continuing the expression or ending it.
+fn starts_operand(operand: Span, tokens: &[Token]) -> bool {
+    match tokens.get(0).map(|t| t.kind) {
+        Some(TokenKind::Identifier) |
+        Some(TokenKind::LIT_Int) |
+        Some(TokenKind::LIT_Real) |
+        Some(TokenKind::LIT_String) |
+        Some(TokenKind::LIT_RawString) |
+        Some(TokenKind::LIT_Codepoint) |
+        Some(TokenKind::LParen) |
+        Some(TokenKind::KEY_Fn) |
+        Some(TokenKind::At) |
+        Some(TokenKind::Dollar) => true,
+        Some(TokenKind::Operator) => is_prefix(operand, &tokens[0], tokens.get(1)),
+        _ => false,
+    }
+}
+
+
+// Whether an operator token after an operand is a prefix operator of
+// another operand: it is if it's bound to what's on its right, but not to
+// the operand on its left, e.g. the `-` of `foo -5`.  Postfix operators
+// are the opposite, e.g. `a++ `.  See `lexer::operator::fixity()`, which
+// decides the same from the source text.
+fn is_prefix(operand: Span, op: &Token, next: Option<&Token>) -> bool {
+    op.kind == TokenKind::Operator && op.span.lo != operand.hi && !is_open_after(op, next)
+}
+
+
+fn is_postfix(operand: Span, op: &Token, next: Option<&Token>) -> bool {
+    op.kind == TokenKind::Operator && op.span.lo == operand.hi && is_open_after(op, next)
+}
+
+
+// Whether the right side of an operator is open, rather than bound to
+// the token after it.
+fn is_open_after(op: &Token, next: Option<&Token>) -> bool {
+    match next {
+        None => true,
+        Some(next) => next.span.lo != op.span.hi || matches!(next.kind,
+            TokenKind::RParen | TokenKind::RSquare | TokenKind::RCurly |
+            TokenKind::Comma | TokenKind::Colon |
+            TokenKind::NewLine | TokenKind::DocComment | TokenKind::EOF
+        ),
+    }
+}
+
+
+fn parse_primary<'s, 't>(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> ParseResult<'s, 't, Expression> {
+    match tokens.get(0).map(|t| t.kind) {
+        // Literals
+        Some(TokenKind::LIT_Int) => {
+            let (lit, rem) = LitInteger::parse(tokens)?;
+            let source = lit.source;
+            return Ok((Expression::new(ExpressionKind::LitInteger(lit), source), rem));
+        }
+        Some(TokenKind::LIT_Real) => {
+            let (lit, rem) = LitReal::parse(tokens)?;
+            let source = lit.source;
+            return Ok((Expression::new(ExpressionKind::LitReal(lit), source), rem));
+        }
+        Some(TokenKind::LIT_String) | Some(TokenKind::LIT_RawString) => {
+            let (lit, rem) = LitString::parse(tokens)?;
+            let source = lit.source;
+            return Ok((Expression::new(ExpressionKind::LitString(lit), source), rem));
         }
-        // Real number literal
-        else if let Ok((lit_real, rem_tokens)) = LitReal::parse(tokens) {
+        Some(TokenKind::LIT_Codepoint) => {
+            let (lit, rem) = LitCodepoint::parse(tokens)?;
+            let source = lit.source;
+            return Ok((Expression::new(ExpressionKind::LitCodepoint(lit), source), rem));
+        }
+
+        // Identifier
+        Some(TokenKind::Identifier) => {
             return Ok((
-                Expression::LitReal(lit_real),
-                rem_tokens,
+                Expression::new(ExpressionKind::Identifier(tokens[0].symbol().unwrap()), tokens[0].span),
+                &tokens[1..],
             ));
         }
+
+        // Group or block
+        Some(TokenKind::LParen) => {
+            let (block, rem) = Block::parse_with(tokens, precedence)?;
+            let source = block.source;
+            return Ok((Expression::new(group_or_block(block), source), rem));
+        }
+
+        // Function literal
+        Some(TokenKind::KEY_Fn) => {
+            let (f, rem) = FnLiteral::parse_with(tokens, precedence)?;
+            let source = f.source;
+            return Ok((Expression::new(ExpressionKind::FnLiteral(f), source), rem));
+        }
+
+        // Return, with a value unless nothing can follow it, e.g. at the
+        // end of the line, or before a "]" or ","
+        Some(TokenKind::KEY_Return) => {
+            let (value, rem) = match tokens.get(1).map(|t| t.kind) {
+                None |
+                Some(TokenKind::RParen) | Some(TokenKind::RSquare) | Some(TokenKind::RCurly) |
+                Some(TokenKind::Comma) | Some(TokenKind::Colon) |
+                Some(TokenKind::NewLine) | Some(TokenKind::DocComment) | Some(TokenKind::EOF) => (None, &tokens[1..]),
+                _ => {
+                    let (value, rem) = Expression::parse_with(&tokens[1..], precedence)?;
+                    (Some(Box::new(value)), rem)
+                }
+            };
+            return Ok((Expression::new(ExpressionKind::Return(value), span_between(tokens, rem)), rem));
+        }
+
         // Error, no successful expression parse
-        else {
-            let ss = if let Some(token) = tokens.get(0) { token.source_span() } else { None };
+        _ => {
+            let ss = tokens.get(0).map(|t| t.span);
             return Err(ParseError {
+                code: codes::UNEXPECTED_TOKEN,
                 message: "Expected expression.".to_string(),
                 source: ss,
+                help: None,
             });
         }
     }
-}
\ No newline at end of file
+}
+
+
+// A block of just one expression is a group, e.g. the `(1 + 4)` of
+// `3 * (1 + 4)`.  Anything else stays a block.
+fn group_or_block(mut block: Block) -> ExpressionKind {
+    if block.statements.len() == 1 {
+        if let Statement::Expression(_) = block.statements[0] {
+            if let Some(Statement::Expression(expr)) = block.statements.pop() {
+                return ExpressionKind::Group(Box::new(expr));
+            }
+        }
+    }
+    return ExpressionKind::Block(block);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::lex_str;
+
+    fn parse(text: &str) -> Result<Expression, String> {
+        parse_with(text, &precedence::BUILTIN)
+    }
+
+    fn parse_with(text: &str, precedence: &PrecedenceTable) -> Result<Expression, String> {
+        let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+        match Expression::parse_with(&tokens[..], precedence) {
+            Ok((expr, rem)) => {
+                assert_eq!(rem[0].kind, TokenKind::EOF, "Unparsed tokens in {:?}", text);
+                Ok(expr)
+            }
+            Err(e) => Err(e.message),
+        }
+    }
+
+    // Describes an expression in prefix notation, for comparing in tests.
+    fn describe(expr: &Expression) -> String {
+        match expr.kind {
+            ExpressionKind::LitInteger(ref lit) => lit.text.clone(),
+            ExpressionKind::LitReal(ref lit) => lit.text.clone(),
+            ExpressionKind::LitString(ref lit) => lit.text.clone(),
+            ExpressionKind::LitCodepoint(ref lit) => lit.text.clone(),
+            ExpressionKind::Identifier(name) => name.as_str().to_string(),
+            ExpressionKind::Group(ref expr) => format!("(group {})", describe(expr)),
+            ExpressionKind::Block(ref block) => format!("(block {})", block.statements.len()),
+            ExpressionKind::FnLiteral(ref f) => format!("(fn {})", f.params.len()),
+            ExpressionKind::Call { ref callee, ref args, syntax } => {
+                let args: Vec<_> = args.iter().map(describe).collect();
+                let call = match syntax {
+                    CallSyntax::Standard => "call",
+                    CallSyntax::Prefix => "prefix-call",
+                    CallSyntax::Infix => "infix-call",
+                    CallSyntax::Method => "method-call",
+                };
+                format!("({} {} [{}])", call, describe(callee), args.join(" "))
+            }
+            ExpressionKind::Prefix { op, ref operand } => format!("({} {})", op.name.as_str(), describe(operand)),
+            ExpressionKind::Postfix { op, ref operand } => format!("({} {})", describe(operand), op.name.as_str()),
+            ExpressionKind::Infix { op, ref left, ref right } |
+            ExpressionKind::Assign { op, target: ref left, value: ref right } => {
+                format!("({} {} {})", op.name.as_str(), describe(left), describe(right))
+            }
+            ExpressionKind::Return(Some(ref value)) => format!("(return {})", describe(value)),
+            ExpressionKind::Return(None) => "(return)".to_string(),
+        }
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(describe(&parse("42").unwrap()), "42");
+        assert_eq!(describe(&parse("4.2").unwrap()), "4.2");
+        assert_eq!(describe(&parse("\"hi\"").unwrap()), "\"hi\"");
+        assert_eq!(describe(&parse("'\"hi\"'").unwrap()), "'\"hi\"'");
+        assert_eq!(describe(&parse("'a'").unwrap()), "'a'");
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(describe(&parse("a + b * c").unwrap()), "(+ a (* b c))");
+        assert_eq!(describe(&parse("a - b - c").unwrap()), "(- (- a b) c)");
+        assert_eq!(describe(&parse("a = b = c + 1").unwrap()), "(= a (= b (+ c 1)))");
+        assert_eq!(describe(&parse("$mem = @d").unwrap()), "(= ($ mem) (@ d))");
+        assert_eq!(describe(&parse("a ==\n\tb").unwrap()), "(== a b)");
+        assert_eq!(describe(&parse("a || b && c == d + e * f").unwrap()), "(|| a (&& b (== c (+ d (* e f)))))");
+        assert_eq!(describe(&parse("a * b - c / d").unwrap()), "(- (* a b) (/ c d))");
+        assert_eq!(describe(&parse("-a * -b").unwrap()), "(* (- a) (- b))");
+
+        // Operators without built-in precedences
+        assert_eq!(describe(&parse("a ++ b + c").unwrap()), "(++ a (+ b c))");
+        assert_eq!(describe(&parse("a ++ b == c").unwrap()), "(== (++ a b) c)");
+    }
+
+    #[test]
+    fn design_examples() {
+        assert_eq!(describe(&parse("1 + 2").unwrap()), "(+ 1 2)");
+        assert_eq!(describe(&parse("a = 1 + 2").unwrap()), "(= a (+ 1 2))");
+        assert_eq!(describe(&parse("a = a - 5").unwrap()), "(= a (- a 5))");
+        assert_eq!(describe(&parse("3 * 1 + 4").unwrap()), "(+ (* 3 1) 4)");
+        assert_eq!(describe(&parse("3 * (1 + 4)").unwrap()), "(* 3 (group (+ 1 4)))");
+        assert_eq!(describe(&parse("a=-1").unwrap()), "(= a (- 1))");
+        assert_eq!(describe(&parse("foo[1, 3]").unwrap()), "(call foo [1 3])");
+    }
+
+    #[test]
+    fn groups() {
+        assert_eq!(describe(&parse("(a)").unwrap()), "(group a)");
+        assert_eq!(describe(&parse("((a + b)) * c").unwrap()), "(* (group (group (+ a b))) c)");
+        assert_eq!(describe(&parse("(\n\ta + b\n) * c").unwrap()), "(* (group (+ a b)) c)");
+        assert_eq!(describe(&parse("()").unwrap()), "(block 0)");
+        assert_eq!(describe(&parse("(val c = 1)").unwrap()), "(block 1)");
+        assert_eq!(describe(&parse("(a\nb) + c").unwrap()), "(+ (block 2) c)");
+    }
+
+    #[test]
+    fn assignment() {
+        match parse("a += b * 2").unwrap().kind {
+            ExpressionKind::Assign { op, ref target, ref value } => {
+                assert_eq!(op.name.as_str(), "+=");
+                assert_eq!(describe(target), "a");
+                assert_eq!(describe(value), "(* b 2)");
+            }
+            ref k => panic!("Expected an assignment, got {:?}", k),
+        }
+        match parse("a == b").unwrap().kind {
+            ExpressionKind::Infix { .. } => {}
+            ref k => panic!("Expected an infix expression, got {:?}", k),
+        }
+        assert_eq!(describe(&parse("a = b += c || d").unwrap()), "(= a (+= b (|| c d)))");
+    }
+
+    #[test]
+    fn postfix() {
+        assert_eq!(describe(&parse("a++").unwrap()), "(a ++)");
+        assert_eq!(describe(&parse("a++ + b").unwrap()), "(+ (a ++) b)");
+        assert_eq!(describe(&parse("-a++").unwrap()), "(- (a ++))");
+        assert_eq!(describe(&parse("foo[a++, b]").unwrap()), "(call foo [(a ++) b])");
+        assert_eq!(describe(&parse("foo[1]++").unwrap()), "((call foo [1]) ++)");
+        assert_eq!(describe(&parse("a+b").unwrap()), "(+ a b)");
+        assert_eq!(describe(&parse("a ++ b").unwrap()), "(++ a b)");
+    }
+
+    #[test]
+    fn custom_precedence() {
+        let mut table = PrecedenceTable::builtin();
+        table.set("+", 11, Associativity::Right);
+        table.set("++", 20, Associativity::Left);
+        assert_eq!(describe(&parse_with("a * b + c", &table).unwrap()), "(* a (+ b c))");
+        assert_eq!(describe(&parse_with("a + b + c", &table).unwrap()), "(+ a (+ b c))");
+        assert_eq!(describe(&parse_with("a * b ++ c", &table).unwrap()), "(* a (++ b c))");
+
+        // The table is used all the way down
+        assert_eq!(describe(&parse_with("(a * b + c)", &table).unwrap()), "(group (* a (+ b c)))");
+        assert_eq!(describe(&parse_with("foo[a * b + c]", &table).unwrap()), "(call foo [(* a (+ b c))])");
+    }
+
+    #[test]
+    fn calls() {
+        assert_eq!(describe(&parse("foo[]").unwrap()), "(call foo [])");
+        assert_eq!(describe(&parse("foo[$ptr, b + 1]").unwrap()), "(call foo [($ ptr) (+ b 1)])");
+        assert_eq!(describe(&parse("foo[1][2]").unwrap()), "(call (call foo [1]) [2])");
+        assert_eq!(describe(&parse("-foo[\n\t1,\n\t2,\n]").unwrap()), "(- (call foo [1 2]))");
+    }
+
+    #[test]
+    fn call_sugar() {
+        // Prefix calls
+        assert_eq!(describe(&parse("foo 5").unwrap()), "(prefix-call foo [5])");
+        assert_eq!(describe(&parse("foo -5").unwrap()), "(prefix-call foo [(- 5)])");
+        assert_eq!(describe(&parse("foo (1 + 2)").unwrap()), "(prefix-call foo [(group (+ 1 2))])");
+        assert_eq!(describe(&parse("foo 5 + 1").unwrap()), "(+ (prefix-call foo [5]) 1)");
+        assert_eq!(describe(&parse("foo bar[5]").unwrap()), "(prefix-call foo [(call bar [5])])");
+        assert_eq!(describe(&parse("-foo[5]").unwrap()), "(- (call foo [5]))");
+
+        // Infix calls
+        assert_eq!(describe(&parse("1 foo 3").unwrap()), "(infix-call foo [1 3])");
+        assert_eq!(describe(&parse("a foo b").unwrap()), "(infix-call foo [a b])");
+        assert_eq!(describe(&parse("1 foo 3 * 2").unwrap()), "(* (infix-call foo [1 3]) 2)");
+        assert_eq!(describe(&parse("a = 1 foo -3").unwrap()), "(= a (infix-call foo [1 (- 3)]))");
+
+        // Method calls
+        assert_eq!(describe(&parse("1.foo[3]").unwrap()), "(method-call foo [1 3])");
+        assert_eq!(describe(&parse("x.foo[]").unwrap()), "(method-call foo [x])");
+        assert_eq!(describe(&parse("x.foo[1].bar[2, 3]").unwrap()), "(method-call bar [(method-call foo [x 1]) 2 3])");
+        assert_eq!(describe(&parse("-x.foo[a + b]").unwrap()), "(- (method-call foo [x (+ a b)]))");
+
+        // Operator spacing decides between call sugar and infix operators
+        assert_eq!(describe(&parse("a - 1").unwrap()), "(- a 1)");
+        assert_eq!(describe(&parse("a-1").unwrap()), "(- a 1)");
+        assert_eq!(describe(&parse("a -1").unwrap()), "(prefix-call a [(- 1)])");
+    }
+
+    #[test]
+    fn call_sugar_errors() {
+        let tokens: Vec<_> = lex_str("double add 1 2").0.into_iter().collect();
+        let e = Expression::parse(&tokens[..]).unwrap_err();
+        assert_eq!(e.code, codes::AMBIGUOUS_CALL);
+        assert_eq!(e.message, "Ambiguous function call sugar: 4 operands in a row can be grouped into calls more than one way.");
+        assert_eq!(e.source.unwrap().range(), 0..14);
+        assert_eq!(e.help.unwrap(), "Use brackets to show which calls are meant, e.g. \"double[add 1 2]\" or \"double add[1, 2]\".");
+
+        let tokens: Vec<_> = lex_str("f (a b) -x y  z").0.into_iter().collect();
+        let e = Expression::parse(&tokens[..]).unwrap_err();
+        assert_eq!(e.help.unwrap(), "Use brackets to show which calls are meant, e.g. \"f[(a b) -x y z]\" or \"f (a b) -x[y, z]\".");
+
+        let tokens: Vec<_> = lex_str("1 2").0.into_iter().collect();
+        let e = Expression::parse(&tokens[..]).unwrap_err();
+        assert_eq!(e.code, codes::CALL_WITHOUT_NAME);
+        assert_eq!(e.message, "Expected a function name to call, as in \"foo 5\".  Is an operator missing?");
+        assert_eq!(e.source.unwrap().range(), 0..1);
+        assert_eq!(e.help, None);
+
+        let tokens: Vec<_> = lex_str("add 1 2").0.into_iter().collect();
+        let e = Expression::parse(&tokens[..]).unwrap_err();
+        assert_eq!(e.code, codes::CALL_WITHOUT_NAME);
+        assert_eq!(e.message, "Expected a function name to call, as in \"1 foo 3\".  Is an operator missing?");
+        assert_eq!(e.source.unwrap().range(), 4..5);
+
+        assert_eq!(parse("foo[1] 2").unwrap_err(), "Expected a function name to call, as in \"foo 5\".  Is an operator missing?");
+        let tokens: Vec<_> = lex_str("foo[1] 2").0.into_iter().collect();
+        let e = Expression::parse(&tokens[..]).unwrap_err();
+        assert_eq!(e.help.unwrap(), "To pass all of the arguments, write \"foo[1, 2]\", or to call the result, \"foo[1][2]\".");
+        let tokens: Vec<_> = lex_str("a foo[b] c").0.into_iter().collect();
+        let e = Expression::parse(&tokens[..]).unwrap_err();
+        assert_eq!(e.help.unwrap(), "To call the result, write \"foo[b][a, c]\".");
+        assert_eq!(parse("x.5").unwrap_err(), "Expected method name after \".\".");
+        assert_eq!(parse("x.foo 5").unwrap_err(), "Expected \"[\" after method name.");
+    }
+
+    #[test]
+    fn returns() {
+        assert_eq!(describe(&parse("return").unwrap()), "(return)");
+        assert_eq!(describe(&parse("return x + y").unwrap()), "(return (+ x y))");
+        assert_eq!(describe(&parse("foo[return]").unwrap()), "(call foo [(return)])");
+        assert_eq!(describe(&parse("foo[return, 1]").unwrap()), "(call foo [(return) 1])");
+        assert_eq!(describe(&parse("(return)").unwrap()), "(group (return))");
+    }
+
+    #[test]
+    fn source() {
+        let expr = parse("a + foo[1]").unwrap();
+        assert_eq!(expr.source.range(), 0..10);
+        match expr.kind {
+            ExpressionKind::Infix { op, ref left, ref right } => {
+                assert_eq!(op.source.range(), 2..3);
+                assert_eq!(left.source.range(), 0..1);
+                assert_eq!(right.source.range(), 4..10);
+            }
+            ref k => panic!("Expected an infix expression, got {:?}", k),
+        }
+    }
+
+    #[test]
+    fn ids() {
+        let mut ids = Vec::new();
+        collect_ids(&parse("foo[a, -b] * (c + 1)").unwrap(), &mut ids);
+        assert_eq!(ids.len(), 10);
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 10);
+    }
+
+    fn collect_ids(expr: &Expression, ids: &mut Vec<NodeId>) {
+        ids.push(expr.id);
+        match expr.kind {
+            ExpressionKind::Call { ref callee, ref args, .. } => {
+                collect_ids(callee, ids);
+                for arg in args {
+                    collect_ids(arg, ids);
+                }
+            }
+            ExpressionKind::Group(ref operand) |
+            ExpressionKind::Prefix { ref operand, .. } |
+            ExpressionKind::Postfix { ref operand, .. } => collect_ids(operand, ids),
+            ExpressionKind::Infix { ref left, ref right, .. } |
+            ExpressionKind::Assign { target: ref left, value: ref right, .. } => {
+                collect_ids(left, ids);
+                collect_ids(right, ids);
+            }
+            ExpressionKind::Block(ref block) => {
+                ids.push(block.id);
+                for statement in &block.statements {
+                    if let Statement::Expression(ref e) = *statement {
+                        collect_ids(e, ids);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(parse("").unwrap_err(), "Expected expression.");
+        assert_eq!(parse("a +").unwrap_err(), "Expected expression.");
+        assert_eq!(parse("foo[1 val]").unwrap_err(), "Expected \",\" or \"]\".");
+    }
+}
@@ -0,0 +1,128 @@
+use super::super::source_map::Span;
+use super::super::symbol::Symbol;
+use super::super::token::{Token, TokenKind};
+use super::{ParseResult, Parseable, expect_token, parse_list, span_between};
+
+use super::block::Block;
+use super::type_expr::TypeExpr;
+use super::node_id::NodeId;
+use super::precedence::{self, PrecedenceTable};
+
+/// A function literal, e.g.
+///
+/// ```text
+/// fn [a: i32, b: i32] -> i32 (
+///     return a + b
+/// )
+/// ```
+#[derive(Clone, Debug)]
+pub struct FnLiteral {
+    pub id: NodeId,
+    pub params: Vec<Param>,
+    pub return_type: Option<TypeExpr>,
+    pub body: Block,
+    pub source: Span,
+}
+
+
+/// A function parameter, e.g. `a: i32`.
+#[derive(Clone, Debug)]
+pub struct Param {
+    pub id: NodeId,
+    pub name: Symbol,
+    pub ty: TypeExpr,
+    pub source: Span,
+}
+
+
+//=============================
+impl<'s, 't> Parseable<'s, 't> for FnLiteral {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        FnLiteral::parse_with(tokens, &precedence::BUILTIN)
+    }
+
+
+    fn parse_with(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> ParseResult<'s, 't, Self> {
+        let (_, rem) = expect_token(tokens, TokenKind::KEY_Fn, "\"fn\"")?;
+        let (_, rem) = expect_token(rem, TokenKind::LSquare, "\"[\" before function parameters")?;
+        let (params, rem) = parse_list(rem, TokenKind::RSquare, Param::parse)?;
+
+        // Return type
+        let (return_type, rem) = match rem.get(0) {
+            Some(t) if t.kind == TokenKind::Operator && t.text == "->" => {
+                let (ty, rem) = TypeExpr::parse(&rem[1..])?;
+                (Some(ty), rem)
+            }
+            _ => (None, rem),
+        };
+
+        let (body, rem) = Block::parse_with(rem, precedence)?;
+        return Ok((
+            FnLiteral {
+                id: NodeId::next(),
+                params: params,
+                return_type: return_type,
+                body: body,
+                source: span_between(tokens, rem),
+            },
+            rem,
+        ));
+    }
+}
+
+
+impl<'s, 't> Parseable<'s, 't> for Param {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        let (name, rem) = expect_token(tokens, TokenKind::Identifier, "parameter name")?;
+        let (_, rem) = expect_token(rem, TokenKind::Colon, "\":\" after parameter name")?;
+        let (ty, rem) = TypeExpr::parse(rem)?;
+        return Ok((
+            Param {
+                id: NodeId::next(),
+                name: name.symbol().unwrap(),
+                ty: ty,
+                source: span_between(tokens, rem),
+            },
+            rem,
+        ));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::lex_str;
+
+    fn parse(text: &str) -> Result<FnLiteral, String> {
+        let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+        match FnLiteral::parse(&tokens[..]) {
+            Ok((f, rem)) => {
+                assert_eq!(rem[0].kind, TokenKind::EOF);
+                Ok(f)
+            }
+            Err(e) => Err(e.message),
+        }
+    }
+
+    #[test]
+    fn fn_literals() {
+        let f = parse("fn [x: i32, y: @i32] -> i32 (\n\treturn x + $y\n)").unwrap();
+        assert_eq!(f.params.len(), 2);
+        assert_eq!(f.params[1].name, Symbol::intern("y"));
+        assert_eq!(f.params[1].source.range(), 12..19);
+        assert!(f.return_type.is_some());
+        assert_eq!(f.body.statements.len(), 1);
+
+        let f = parse("fn [] ()").unwrap();
+        assert_eq!(f.params.len(), 0);
+        assert!(f.return_type.is_none());
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(parse("fn (x)").unwrap_err(), "Expected \"[\" before function parameters.");
+        assert_eq!(parse("fn [x] ()").unwrap_err(), "Expected \":\" after parameter name.");
+        assert_eq!(parse("fn [] -> i32").unwrap_err(), "Expected \"(\".");
+    }
+}
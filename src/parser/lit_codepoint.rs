@@ -0,0 +1,162 @@
+use super::super::source_map::Span;
+use super::super::token::{Token, TokenKind};
+use super::lit_string::decode_escape;
+use super::{ParseResult, ParseError, Parseable};
+use super::super::diagnostics::codes;
+
+/// A codepoint literal, e.g. `'a'` or `'\u{1F600}'`.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct LitCodepoint {
+    pub text: String,
+    pub source: Span,
+}
+
+impl LitCodepoint {
+    /// Decodes the value of the literal as a Unicode scalar value.
+    ///
+    /// The literal must contain exactly one codepoint, either as-is or as
+    /// an escape sequence (the same ones as in string literals).
+    pub fn value(&self) -> Result<u32, ParseError> {
+        // Strip the ticks.  An unterminated literal has no closing tick,
+        // in which case we just go to the end.
+        let contents = &self.text[1..];
+        let end = match find_closing_tick(contents) {
+            Some(i) => i,
+            None => contents.len(),
+        };
+        let contents = &contents[..end];
+        
+        // Decode the codepoint
+        let (c, len) = if contents.starts_with("\\") {
+            match decode_escape(contents) {
+                Ok(r) => r,
+                Err((message, len)) => return Err(ParseError {
+                    code: codes::INVALID_ESCAPE,
+                    message: message,
+                    source: Some(self.source.sub_span(1, 1 + len)),
+                    help: None,
+                }),
+            }
+        }
+        else {
+            match contents.chars().next() {
+                Some(c) => (c, c.len_utf8()),
+                None => return Err(ParseError {
+                    code: codes::INVALID_CODEPOINT_LITERAL,
+                    message: "Empty codepoint literal.".to_string(),
+                    source: Some(self.source),
+                    help: None,
+                }),
+            }
+        };
+        
+        // Make sure there's nothing left over
+        if len < contents.len() {
+            return Err(ParseError {
+                code: codes::INVALID_CODEPOINT_LITERAL,
+                message: "Codepoint literals must contain exactly one codepoint.".to_string(),
+                source: Some(self.source),
+                help: None,
+            });
+        }
+        
+        return Ok(c as u32);
+    }
+}
+
+
+// Finds the byte index of the first unescaped tick in the text.
+fn find_closing_tick(text: &str) -> Option<usize> {
+    let mut last_was_esc = false;
+    for (b, c) in text.char_indices() {
+        if last_was_esc {
+            last_was_esc = false;
+        }
+        else if c == '\\' {
+            last_was_esc = true;
+        }
+        else if c == '\'' {
+            return Some(b);
+        }
+    }
+    return None;
+}
+
+
+//=============================
+impl<'s, 't> Parseable<'s, 't> for LitCodepoint {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {    
+        // Attempt to parse a codepoint literal
+        if let Some(&Token { kind: TokenKind::LIT_Codepoint, text: t, span: s, .. }) = tokens.get(0) {
+            return Ok((
+                LitCodepoint {
+                    text: t.to_string(),
+                    source: s,
+                },
+                &tokens[1..],
+            ));
+        }
+        // Return error if failed
+        else {
+            let ss = tokens.get(0).map(|t| t.span);
+            return Err(ParseError {
+                code: codes::UNEXPECTED_TOKEN,
+                message: "Expected codepoint literal.".to_string(),
+                source: ss,
+                help: None,
+            });
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Parseable;
+    use lexer::lex_str;
+    
+    fn parse_value(text: &str) -> Result<u32, (String, String)> {
+        let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+        let (lit, _) = LitCodepoint::parse(&tokens[..]).unwrap();
+        return lit.value().map_err(|e| (e.message, text[e.source.unwrap().range()].to_string()));
+    }
+    
+    #[test]
+    fn values() {
+        assert_eq!(parse_value("'a'"), Ok(0x61));
+        assert_eq!(parse_value("'速'"), Ok(0x901F));
+        assert_eq!(parse_value("'😀'"), Ok(0x1F600));
+        assert_eq!(parse_value(r"'\n'"), Ok(0x0A));
+        assert_eq!(parse_value(r"'\''"), Ok(0x27));
+        assert_eq!(parse_value(r#"'\"'"#), Ok(0x22));
+        assert_eq!(parse_value(r"'\x41'"), Ok(0x41));
+        assert_eq!(parse_value(r"'\u{1F600}'"), Ok(0x1F600));
+    }
+    
+    #[test]
+    fn invalid() {
+        assert_eq!(parse_value("''"), Err(("Empty codepoint literal.".to_string(), "''".to_string())));
+        assert_eq!(parse_value("'ab'"), Err(("Codepoint literals must contain exactly one codepoint.".to_string(), "'ab'".to_string())));
+        // A decomposed "é" is two codepoints.
+        assert_eq!(parse_value("'e\u{301}'"), Err(("Codepoint literals must contain exactly one codepoint.".to_string(), "'e\u{301}'".to_string())));
+        assert_eq!(parse_value(r"'\q'"), Err(("Unknown escape sequence \"\\q\".".to_string(), r"\q".to_string())));
+        assert_eq!(parse_value(r"'\u{D800}'"), Err(("Unicode escape is not a valid Unicode scalar value.".to_string(), r"\u{D800}".to_string())));
+    }
+    
+    #[test]
+    fn error_codes() {
+        let code = |text: &str| {
+            let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+            LitCodepoint::parse(&tokens[..]).unwrap().0.value().unwrap_err().code
+        };
+        assert_eq!(code("''"), codes::INVALID_CODEPOINT_LITERAL);
+        assert_eq!(code("'ab'"), codes::INVALID_CODEPOINT_LITERAL);
+        assert_eq!(code(r"'\q'"), codes::INVALID_ESCAPE);
+    }
+    
+    #[test]
+    fn unterminated() {
+        assert_eq!(parse_value("'a"), Ok(0x61));
+    }
+}
@@ -1,22 +1,75 @@
-use std::collections::VecDeque;
-use super::super::source_span::SourceSpan;
-use super::super::token::Token;
+use super::super::source_map::Span;
+use super::super::token::{Token, TokenKind};
+use super::super::lexer::number::{split_number, NumberType};
 use super::{ParseResult, ParseError, Parseable};
+use super::super::diagnostics::codes;
 
-#[derive(Eq, PartialEq, Copy, Clone, Debug)]
-pub struct LitInteger<'a> {
-    pub text: &'a str,
-    pub source: SourceSpan<'a>,
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct LitInteger {
+    pub text: String,
+    pub source: Span,
+}
+
+impl LitInteger {
+    /// The type given by the literal's suffix, if it has one.
+    pub fn suffix_type(&self) -> Option<NumberType> {
+        split_number(&self.text).suffix_type()
+    }
+    
+    /// Decodes the value of the literal.
+    ///
+    /// Returns an error if the value doesn't fit in the literal's suffix
+    /// type, or in a u64 if it doesn't have a suffix.
+    pub fn value(&self) -> Result<u64, ParseError> {
+        let parts = split_number(&self.text);
+        let ty = parts.suffix_type();
+        let too_large = ParseError {
+            code: codes::NUMBER_OUT_OF_RANGE,
+            message: match ty {
+                Some(t) => format!("Integer literal is too large for {}.", t.name()),
+                None => "Integer literal is too large.".to_string(),
+            },
+            source: Some(self.source),
+            help: None,
+        };
+        
+        // Parse the digits
+        let mut value: u64 = 0;
+        for c in parts.digits.chars().filter(|&c| c != '_') {
+            let digit = match c.to_digit(parts.radix) {
+                Some(d) => d as u64,
+                None => return Err(ParseError {
+                    code: codes::INVALID_NUMBER,
+                    message: "Invalid integer literal.".to_string(),
+                    source: Some(self.source),
+                    help: None,
+                }),
+            };
+            value = match value.checked_mul(parts.radix as u64).and_then(|v| v.checked_add(digit)) {
+                Some(v) => v,
+                None => return Err(too_large),
+            };
+        }
+        
+        // Check that it fits its type
+        if let Some(max) = ty.and_then(|t| t.max_integer()) {
+            if value > max {
+                return Err(too_large);
+            }
+        }
+        
+        return Ok(value);
+    }
 }
 
 //=============================
-impl<'a> Parseable<'a> for LitInteger<'a> {
-    fn parse(tokens: &'a [Token<'a>]) -> ParseResult<'a, Self> {    
+impl<'s, 't> Parseable<'s, 't> for LitInteger {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {    
         // Attempt to parse an integer literal
-        if let Some(&Token::LIT_Int(s)) = tokens.get(0) {
+        if let Some(&Token { kind: TokenKind::LIT_Int, text: t, span: s, .. }) = tokens.get(0) {
             return Ok((
                 LitInteger {
-                    text: s.span,
+                    text: t.to_string(),
                     source: s,
                 },
                 &tokens[1..],
@@ -24,11 +77,55 @@ impl<'a> Parseable<'a> for LitInteger<'a> {
         }
         // Return error if failed
         else {
-            let ss = if let Some(token) = tokens.get(0) { token.source_span() } else { None };
+            let ss = tokens.get(0).map(|t| t.span);
             return Err(ParseError {
+                code: codes::UNEXPECTED_TOKEN,
                 message: "Expected integer literal.".to_string(),
                 source: ss,
+                help: None,
             });
         }
     }
-}
\ No newline at end of file
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Parseable;
+    use lexer::lex_str;
+    use lexer::number::NumberType;
+    
+    fn parse_value(text: &str) -> Result<u64, String> {
+        let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+        let (lit, _) = LitInteger::parse(&tokens[..]).unwrap();
+        return lit.value().map_err(|e| e.message);
+    }
+    
+    #[test]
+    fn values() {
+        assert_eq!(parse_value("42"), Ok(42));
+        assert_eq!(parse_value("1_000_000"), Ok(1000000));
+        assert_eq!(parse_value("0xFF_ff"), Ok(0xFFFF));
+        assert_eq!(parse_value("0o777"), Ok(0o777));
+        assert_eq!(parse_value("0b1010"), Ok(10));
+        assert_eq!(parse_value("255u8"), Ok(255));
+        assert_eq!(parse_value("18446744073709551615"), Ok(u64::MAX));
+    }
+    
+    #[test]
+    fn overflow() {
+        assert_eq!(parse_value("256u8"), Err("Integer literal is too large for u8.".to_string()));
+        assert_eq!(parse_value("128i8"), Err("Integer literal is too large for i8.".to_string()));
+        assert_eq!(parse_value("0x8000_0000i32"), Err("Integer literal is too large for i32.".to_string()));
+        assert_eq!(parse_value("18446744073709551616"), Err("Integer literal is too large.".to_string()));
+        assert_eq!(parse_value("127i8"), Ok(127));
+    }
+    
+    #[test]
+    fn suffix_type() {
+        let tokens: Vec<_> = lex_str("7u16").0.into_iter().collect();
+        let (lit, _) = LitInteger::parse(&tokens[..]).unwrap();
+        assert_eq!(lit.suffix_type(), Some(NumberType::U16));
+    }
+}
@@ -1,22 +1,62 @@
-use std::collections::VecDeque;
-use super::super::source_span::SourceSpan;
-use super::super::token::Token;
+use super::super::source_map::Span;
+use super::super::token::{Token, TokenKind};
+use super::super::lexer::number::{split_number, NumberType};
 use super::{ParseResult, ParseError, Parseable};
+use super::super::diagnostics::codes;
 
-#[derive(Eq, PartialEq, Copy, Clone, Debug)]
-pub struct LitReal<'a> {
-    pub text: &'a str,
-    pub source: SourceSpan<'a>,
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct LitReal {
+    pub text: String,
+    pub source: Span,
+}
+
+impl LitReal {
+    /// The type given by the literal's suffix, if it has one.
+    pub fn suffix_type(&self) -> Option<NumberType> {
+        split_number(&self.text).suffix_type()
+    }
+    
+    /// Decodes the value of the literal.
+    ///
+    /// Returns an error if the value is too large to be represented by the
+    /// literal's suffix type, or by an f64 if it doesn't have a suffix.
+    pub fn value(&self) -> Result<f64, ParseError> {
+        let parts = split_number(&self.text);
+        let ty = parts.suffix_type();
+        
+        let value: f64 = match parts.clean_digits().parse() {
+            Ok(v) => v,
+            Err(_) => return Err(ParseError {
+                code: codes::INVALID_NUMBER,
+                message: "Invalid real number literal.".to_string(),
+                source: Some(self.source),
+                help: None,
+            }),
+        };
+        
+        // Values that round to the type's largest value are fine
+        let threshold = ty.and_then(|t| t.real_overflow_threshold()).unwrap_or(f64::INFINITY);
+        if value >= threshold {
+            return Err(ParseError {
+                code: codes::NUMBER_OUT_OF_RANGE,
+                message: format!("Real number literal is too large for {}.", ty.unwrap_or(NumberType::F64).name()),
+                source: Some(self.source),
+                help: None,
+            });
+        }
+        
+        return Ok(value);
+    }
 }
 
 //=============================
-impl<'a> Parseable<'a> for LitReal<'a> {
-    fn parse(tokens: &'a [Token<'a>]) -> ParseResult<'a, Self> {    
+impl<'s, 't> Parseable<'s, 't> for LitReal {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {    
         // Attempt to parse an real literal
-        if let Some(&Token::LIT_Real(s)) = tokens.get(0) {
+        if let Some(&Token { kind: TokenKind::LIT_Real, text: t, span: s, .. }) = tokens.get(0) {
             return Ok((
                 LitReal {
-                    text: s.span,
+                    text: t.to_string(),
                     source: s,
                 },
                 &tokens[1..],
@@ -24,13 +64,51 @@ impl<'a> Parseable<'a> for LitReal<'a> {
         }
         // Return error if failed
         else {
-            let ss = if let Some(token) = tokens.get(0) { token.source_span() } else { None };
+            let ss = tokens.get(0).map(|t| t.span);
             return Err(ParseError {
+                code: codes::UNEXPECTED_TOKEN,
                 message: "Expected real number literal.".to_string(),
                 source: ss,
+                help: None,
             });
         }
     }
 }
 
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::Parseable;
+    use lexer::lex_str;
+    
+    fn parse_value(text: &str) -> Result<f64, String> {
+        let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+        let (lit, _) = LitReal::parse(&tokens[..]).unwrap();
+        return lit.value().map_err(|e| e.message);
+    }
+    
+    #[test]
+    fn values() {
+        assert_eq!(parse_value("1.5"), Ok(1.5));
+        assert_eq!(parse_value("1_000.25"), Ok(1000.25));
+        assert_eq!(parse_value("1.5e-3"), Ok(1.5e-3));
+        assert_eq!(parse_value("2E10"), Ok(2e10));
+        assert_eq!(parse_value("1.0f32"), Ok(1.0));
+        assert_eq!(parse_value("3f64"), Ok(3.0));
+    }
+    
+    #[test]
+    fn overflow() {
+        assert_eq!(parse_value("65504.0f16"), Ok(65504.0));
+        assert_eq!(parse_value("65505.0f16"), Ok(65505.0));
+        assert_eq!(parse_value("65519.99f16"), Ok(65519.99));
+        assert_eq!(parse_value("3.4028235e38f32"), Ok(3.4028235e38));
+        assert_eq!(parse_value("3.40282356e38f32"), Ok(3.40282356e38));
+        assert_eq!(parse_value("3.4028236e38f32"), Err("Real number literal is too large for f32.".to_string()));
+        assert_eq!(parse_value("1.7976931348623158e308"), Ok(f64::MAX));
+        assert_eq!(parse_value("65520.0f16"), Err("Real number literal is too large for f16.".to_string()));
+        assert_eq!(parse_value("1e39f32"), Err("Real number literal is too large for f32.".to_string()));
+        assert_eq!(parse_value("1e309"), Err("Real number literal is too large for f64.".to_string()));
+    }
+}
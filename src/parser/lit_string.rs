@@ -0,0 +1,295 @@
+use std::borrow::Cow;
+use super::super::source_map::Span;
+use super::super::token::{Token, TokenKind};
+use super::{ParseResult, ParseError, Parseable};
+use super::super::diagnostics::codes;
+
+/// A string literal, either a normal one or a raw one.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct LitString {
+    pub text: String,
+    pub source: Span,
+    pub is_raw: bool,
+}
+
+impl LitString {
+    /// Decodes the value of the literal.
+    ///
+    /// Normal string literals have their escape sequences decoded, and if
+    /// any of them are invalid an error is returned for each, with the
+    /// span of just the offending escape sequence.  Raw string literals
+    /// are passed through verbatim, minus their delimiters.
+    pub fn value(&self) -> Result<Cow<'_, str>, Vec<ParseError>> {
+        if self.is_raw {
+            return Ok(Cow::Borrowed(self.raw_contents()));
+        }
+        
+        // Skip the opening quote, and stop at the closing one.  An
+        // unterminated string literal has no closing quote, in which case
+        // we just go to the end.
+        let contents = &self.text[1..];
+        let end = match find_closing_quote(contents) {
+            Some(i) => i,
+            None => contents.len(),
+        };
+        let contents = &contents[..end];
+        
+        // Fast path: nothing to decode
+        if !contents.contains('\\') {
+            return Ok(Cow::Borrowed(contents));
+        }
+        
+        let mut value = String::with_capacity(contents.len());
+        let mut errors = Vec::new();
+        let mut i = 0;
+        while i < contents.len() {
+            let c = contents[i..].chars().next().unwrap();
+            if c == '\\' {
+                match decode_escape(&contents[i..]) {
+                    Ok((decoded, len)) => {
+                        value.push(decoded);
+                        i += len;
+                    }
+                    Err((message, len)) => {
+                        errors.push(ParseError {
+                            code: codes::INVALID_ESCAPE,
+                            message: message,
+                            source: Some(self.source.sub_span(1 + i, 1 + i + len)),
+                            help: None,
+                        });
+                        i += len;
+                    }
+                }
+            }
+            else {
+                value.push(c);
+                i += c.len_utf8();
+            }
+        }
+        
+        if errors.len() > 0 {
+            return Err(errors);
+        }
+        return Ok(Cow::Owned(value));
+    }
+    
+    
+    // The contents of a raw string literal, without the tick-delimiters
+    // and quotes.
+    fn raw_contents(&self) -> &str {
+        let tick_count = self.text.chars().take_while(|&c| c == '\'').count();
+        let contents = &self.text[tick_count + 1..];
+        
+        // Strip the closing delimiter, if the literal is terminated
+        let closing_len = tick_count + 1;
+        let is_closed = contents.len() >= closing_len
+            && contents.ends_with(&self.text[..tick_count])
+            && contents[..contents.len() - tick_count].ends_with("\"");
+        if is_closed {
+            return &contents[..contents.len() - closing_len];
+        }
+        return contents;
+    }
+}
+
+
+/// Decodes the escape sequence at the start of `text`, which must start
+/// with a backslash.
+///
+/// On success returns the decoded character and the length in bytes of
+/// the escape sequence.  On failure returns an error message and the
+/// length in bytes of the invalid part of the text.
+pub fn decode_escape(text: &str) -> Result<(char, usize), (String, usize)> {
+    let mut chars = text[1..].chars();
+    let c = match chars.next() {
+        Some(c) => c,
+        None => return Err(("Incomplete escape sequence.".to_string(), 1)),
+    };
+    
+    match c {
+        'n' => Ok(('\n', 2)),
+        't' => Ok(('\t', 2)),
+        'r' => Ok(('\r', 2)),
+        '0' => Ok(('\0', 2)),
+        '\\' => Ok(('\\', 2)),
+        '"' => Ok(('"', 2)),
+        '\'' => Ok(('\'', 2)),
+        
+        // Byte escape, e.g. \x41
+        'x' => {
+            let digits: String = text[2..].chars().take(2).take_while(|c| c.is_ascii_hexdigit()).collect();
+            let len = 2 + digits.len();
+            if digits.len() < 2 {
+                return Err(("Byte escapes need exactly two hex digits, e.g. \\x41.".to_string(), len));
+            }
+            let value = u8::from_str_radix(&digits[..], 16).unwrap();
+            if value > 0x7F {
+                return Err(("Byte escapes only go up to \\x7F.  Use \\u{...} for non-ASCII characters.".to_string(), len));
+            }
+            return Ok((value as char, len));
+        }
+        
+        // Unicode escape, e.g. \u{1F600}
+        'u' => {
+            if !text[2..].starts_with("{") {
+                return Err(("Unicode escapes need braces, e.g. \\u{1F600}.".to_string(), 2));
+            }
+            let digits: String = text[3..].chars().take_while(|c| c.is_ascii_hexdigit()).collect();
+            let len = 3 + digits.len();
+            if !text[len..].starts_with("}") {
+                return Err(("Unterminated or malformed unicode escape.".to_string(), len));
+            }
+            let len = len + 1;
+            if digits.len() == 0 || digits.len() > 6 {
+                return Err(("Unicode escapes need between one and six hex digits.".to_string(), len));
+            }
+            match ::std::char::from_u32(u32::from_str_radix(&digits[..], 16).unwrap()) {
+                Some(c) => return Ok((c, len)),
+                None => return Err(("Unicode escape is not a valid Unicode scalar value.".to_string(), len)),
+            }
+        }
+        
+        _ => Err((format!("Unknown escape sequence \"\\{}\".", c), 1 + c.len_utf8())),
+    }
+}
+
+
+// Finds the byte index of the first unescaped double quote in the text.
+fn find_closing_quote(text: &str) -> Option<usize> {
+    let mut last_was_esc = false;
+    for (b, c) in text.char_indices() {
+        if last_was_esc {
+            last_was_esc = false;
+        }
+        else if c == '\\' {
+            last_was_esc = true;
+        }
+        else if c == '"' {
+            return Some(b);
+        }
+    }
+    return None;
+}
+
+
+//=============================
+impl<'s, 't> Parseable<'s, 't> for LitString {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {    
+        // Attempt to parse a string literal
+        match tokens.get(0) {
+            Some(&Token { kind: TokenKind::LIT_String, text: t, span: s, .. }) => {
+                return Ok((
+                    LitString {
+                        text: t.to_string(),
+                        source: s,
+                        is_raw: false,
+                    },
+                    &tokens[1..],
+                ));
+            }
+            Some(&Token { kind: TokenKind::LIT_RawString, text: t, span: s, .. }) => {
+                return Ok((
+                    LitString {
+                        text: t.to_string(),
+                        source: s,
+                        is_raw: true,
+                    },
+                    &tokens[1..],
+                ));
+            }
+            // Return error if failed
+            _ => {
+                let ss = tokens.get(0).map(|t| t.span);
+                return Err(ParseError {
+                    code: codes::UNEXPECTED_TOKEN,
+                    message: "Expected string literal.".to_string(),
+                    source: ss,
+                    help: None,
+                });
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use super::*;
+    use super::super::Parseable;
+    use lexer::lex_str;
+    use source_map::SourceMap;
+    
+    fn parse_value(text: &str) -> Result<String, Vec<(String, String, u32, u32)>> {
+        let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+        let (lit, _) = LitString::parse(&tokens[..]).unwrap();
+        let mut map = SourceMap::new();
+        map.add_file("test.rune", text.to_string());
+        return match lit.value() {
+            Ok(v) => Ok(v.into_owned()),
+            Err(errors) => Err(errors.iter().map(|e| {
+                let ss = e.source.unwrap();
+                let pos = map.line_col(ss);
+                (e.message.clone(), map.text(ss).to_string(), pos.line, pos.column)
+            }).collect()),
+        };
+    }
+    
+    #[test]
+    fn plain() {
+        let tokens: Vec<_> = lex_str("\"Hello there\"").0.into_iter().collect();
+        let (lit, _) = LitString::parse(&tokens[..]).unwrap();
+        match lit.value() {
+            Ok(Cow::Borrowed(s)) => assert_eq!(s, "Hello there"),
+            _ => panic!("Expected a borrowed string."),
+        }
+    }
+    
+    #[test]
+    fn escapes() {
+        assert_eq!(parse_value(r#""a\nb\tc\\d\"e\r\0\'""#), Ok("a\nb\tc\\d\"e\r\0'".to_string()));
+        assert_eq!(parse_value(r#""\x41\x7f""#), Ok("A\x7f".to_string()));
+        assert_eq!(parse_value(r#""\u{1F600} \u{e9}""#), Ok("😀 é".to_string()));
+        assert_eq!(parse_value("\"größe\\n\""), Ok("größe\n".to_string()));
+    }
+    
+    #[test]
+    fn invalid_escapes() {
+        assert_eq!(parse_value(r#""ab\qcd""#), Err(vec![
+            ("Unknown escape sequence \"\\q\".".to_string(), r"\q".to_string(), 0, 3),
+        ]));
+        assert_eq!(parse_value(r#""\x4g \x80""#), Err(vec![
+            ("Byte escapes need exactly two hex digits, e.g. \\x41.".to_string(), r"\x4".to_string(), 0, 1),
+            ("Byte escapes only go up to \\x7F.  Use \\u{...} for non-ASCII characters.".to_string(), r"\x80".to_string(), 0, 6),
+        ]));
+        assert_eq!(parse_value(r#""\u41 \u{41 \u{} \u{D800} \u{1234567}""#), Err(vec![
+            ("Unicode escapes need braces, e.g. \\u{1F600}.".to_string(), r"\u".to_string(), 0, 1),
+            ("Unterminated or malformed unicode escape.".to_string(), r"\u{41".to_string(), 0, 6),
+            ("Unicode escapes need between one and six hex digits.".to_string(), r"\u{}".to_string(), 0, 12),
+            ("Unicode escape is not a valid Unicode scalar value.".to_string(), r"\u{D800}".to_string(), 0, 17),
+            ("Unicode escapes need between one and six hex digits.".to_string(), r"\u{1234567}".to_string(), 0, 26),
+        ]));
+    }
+    
+    #[test]
+    fn invalid_escape_position() {
+        // The error points right at the escape, even on a later line of a
+        // multi-line string.
+        assert_eq!(parse_value("\"größe\n  a\\q\""), Err(vec![
+            ("Unknown escape sequence \"\\q\".".to_string(), r"\q".to_string(), 1, 3),
+        ]));
+    }
+    
+    #[test]
+    fn unterminated() {
+        assert_eq!(parse_value("\"abc\\n"), Ok("abc\n".to_string()));
+    }
+    
+    #[test]
+    fn raw() {
+        assert_eq!(parse_value(r#"'"C:\new\"'"#), Ok(r#"C:\new\"#.to_string()));
+        assert_eq!(parse_value(r#"''"Say "'hi'"!"''"#), Ok(r#"Say "'hi'"!"#.to_string()));
+        assert_eq!(parse_value("'\"\n\"'"), Ok("\n".to_string()));
+        assert_eq!(parse_value("''\"unterminated\"'"), Ok("unterminated\"'".to_string()));
+    }
+}
@@ -0,0 +1,209 @@
+//! Checking that the literals of a parse tree decode.
+//!
+//! Literals are kept as source text in the tree, and decoded on demand, so
+//! errors such as out of range numbers and invalid escape sequences aren't
+//! found by parsing alone.
+
+use super::ParseError;
+use super::ParseTree;
+use super::namespace::Namespace;
+use super::declaration::Declaration;
+use super::block::{Block, Statement};
+use super::expression::{Expression, ExpressionKind};
+use super::fn_literal::FnLiteral;
+use super::type_expr::{TypeExpr, TypeExprKind};
+
+/// Decodes every literal in a parse tree, returning the errors from the
+/// ones that don't decode, in source order.
+pub fn check_literals(tree: &ParseTree) -> Vec<ParseError> {
+    let mut errors = Vec::new();
+    if let ParseTree::Root(ref ns) = *tree {
+        check_namespace(ns, &mut errors);
+    }
+    errors
+}
+
+
+fn check_namespace(ns: &Namespace, errors: &mut Vec<ParseError>) {
+    for decl in &ns.decls {
+        check_declaration(decl, errors);
+    }
+}
+
+
+fn check_declaration(decl: &Declaration, errors: &mut Vec<ParseError>) {
+    match *decl {
+        Declaration::Namespace(ref ns) => check_namespace(ns, errors),
+        Declaration::Type(ref d) => check_type_expr(&d.ty, errors),
+        Declaration::Const(ref d) => {
+            check_optional_type_expr(&d.ty, errors);
+            check_expression(&d.init, errors);
+        }
+        Declaration::Val(ref d) => {
+            check_optional_type_expr(&d.ty, errors);
+            check_expression(&d.init, errors);
+        }
+        Declaration::Var(ref d) => {
+            check_optional_type_expr(&d.ty, errors);
+            if let Some(ref init) = d.init {
+                check_expression(init, errors);
+            }
+        }
+    }
+}
+
+
+fn check_block(block: &Block, errors: &mut Vec<ParseError>) {
+    for statement in &block.statements {
+        match *statement {
+            Statement::Declaration(ref d) => check_declaration(d, errors),
+            Statement::Expression(ref e) => check_expression(e, errors),
+        }
+    }
+}
+
+
+fn check_fn_literal(f: &FnLiteral, errors: &mut Vec<ParseError>) {
+    for param in &f.params {
+        check_type_expr(&param.ty, errors);
+    }
+    check_optional_type_expr(&f.return_type, errors);
+    check_block(&f.body, errors);
+}
+
+
+fn check_expression(expr: &Expression, errors: &mut Vec<ParseError>) {
+    match expr.kind {
+        ExpressionKind::LitInteger(ref lit) => {
+            if let Err(e) = lit.value() {
+                errors.push(e);
+            }
+        }
+        ExpressionKind::LitReal(ref lit) => {
+            if let Err(e) = lit.value() {
+                errors.push(e);
+            }
+        }
+        ExpressionKind::LitString(ref lit) => {
+            if let Err(e) = lit.value() {
+                errors.extend(e);
+            }
+        }
+        ExpressionKind::LitCodepoint(ref lit) => {
+            if let Err(e) = lit.value() {
+                errors.push(e);
+            }
+        }
+        ExpressionKind::Identifier(_) => {}
+        ExpressionKind::Group(ref e) => check_expression(e, errors),
+        ExpressionKind::Block(ref b) => check_block(b, errors),
+        ExpressionKind::FnLiteral(ref f) => check_fn_literal(f, errors),
+        ExpressionKind::Call { ref callee, ref args, .. } => {
+            // Method calls have the receiver as their first argument, which
+            // comes before the method's name in the source
+            let mut exprs: Vec<&Expression> = args.iter().chain(Some(&**callee)).collect();
+            exprs.sort_by_key(|e| e.source.lo);
+            for e in exprs {
+                check_expression(e, errors);
+            }
+        }
+        ExpressionKind::Prefix { ref operand, .. } |
+        ExpressionKind::Postfix { ref operand, .. } => check_expression(operand, errors),
+        ExpressionKind::Infix { ref left, ref right, .. } => {
+            check_expression(left, errors);
+            check_expression(right, errors);
+        }
+        ExpressionKind::Assign { ref target, ref value, .. } => {
+            check_expression(target, errors);
+            check_expression(value, errors);
+        }
+        ExpressionKind::Return(ref value) => {
+            if let Some(ref value) = *value {
+                check_expression(value, errors);
+            }
+        }
+    }
+}
+
+
+fn check_type_expr(ty: &TypeExpr, errors: &mut Vec<ParseError>) {
+    match ty.kind {
+        TypeExprKind::Named(_) => {}
+        TypeExprKind::Pointer(ref target) => check_type_expr(target, errors),
+        TypeExprKind::Slice(ref element) => check_type_expr(element, errors),
+        TypeExprKind::Array { ref length, ref element } => {
+            check_expression(length, errors);
+            check_type_expr(element, errors);
+        }
+        TypeExprKind::Tuple(ref elements) => {
+            for element in elements {
+                check_type_expr(element, errors);
+            }
+        }
+        TypeExprKind::Struct(ref fields) => {
+            for field in fields {
+                check_type_expr(&field.ty, errors);
+            }
+        }
+    }
+}
+
+
+fn check_optional_type_expr(ty: &Option<TypeExpr>, errors: &mut Vec<ParseError>) {
+    if let Some(ref ty) = *ty {
+        check_type_expr(ty, errors);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::parse_file;
+    use diagnostics::codes;
+    use lexer::lex_str;
+
+    fn check(text: &str) -> Vec<ParseError> {
+        let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+        let (tree, errors) = parse_file(&tokens[..]);
+        assert_eq!(errors.len(), 0);
+        check_literals(&tree)
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(check("val a = 255u8\nval b = 1.5 + 'a'\nval c = \"a\\tb\"\n").len(), 0);
+        assert_eq!(check("").len(), 0);
+    }
+
+    #[test]
+    fn invalid() {
+        let text = "val small = 256u8\nval a = 1.0e400f64\nval b = '\\q'\nval c = 'ab'\nval d = \"\\q\"\n";
+        let errors = check(text);
+        let codes: Vec<_> = errors.iter().map(|e| e.code).collect();
+        assert_eq!(codes, vec![
+            codes::NUMBER_OUT_OF_RANGE,
+            codes::NUMBER_OUT_OF_RANGE,
+            codes::INVALID_ESCAPE,
+            codes::INVALID_CODEPOINT_LITERAL,
+            codes::INVALID_ESCAPE,
+        ]);
+        assert_eq!(errors[0].source.unwrap().range(), 12..17);
+    }
+
+    #[test]
+    fn nested() {
+        let text = "namespace (\n\
+                    \tval f = fn [a: [300u8]i32] -> i32 (\n\
+                    \t\treturn x.foo[256u8] + -'ab'\n\
+                    \t)\n\
+                    )\n";
+        let errors = check(text);
+        let codes: Vec<_> = errors.iter().map(|e| e.code).collect();
+        assert_eq!(codes, vec![
+            codes::NUMBER_OUT_OF_RANGE,
+            codes::NUMBER_OUT_OF_RANGE,
+            codes::INVALID_CODEPOINT_LITERAL,
+        ]);
+    }
+}
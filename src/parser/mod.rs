@@ -1,47 +1,289 @@
+//! Parsing tokens into a parse tree.
+//!
+//! `parse_file()` parses a whole file.  Each node of the tree implements
+//! `Parseable`, which parses it from the start of a token slice.  Compiler
+//! directives are parsed separately, with `parse_directives()`.  Literals
+//! are only decoded on demand, or all at once by `check_literals()`.
+//!
+//! The tree owns all of its nodes, so it outlives the tokens it's parsed
+//! from.  Every node has a `NodeId` and a source span: later passes keep
+//! what they work out about nodes in side tables keyed by id (`NodeMap`),
+//! rather than in the tree itself.
+
 mod declaration;
+mod directive;
 mod expression;
+mod block;
+mod fn_literal;
+mod type_expr;
 mod namespace;
 mod lit_integer;
 mod lit_real;
+mod lit_string;
+mod lit_codepoint;
+mod literals;
+mod node_id;
+mod precedence;
+
+use source_map::Span;
+use diagnostics::{codes, Diagnostic, ErrorCode};
+use token::{Token, TokenKind};
 
-use std::fmt::{Display, Formatter, Error};
-use source_span::SourceSpan;
-use token::Token;
-use self::namespace::Namespace;
+pub use self::declaration::{Declaration, TypeDecl, ConstDecl, ValDecl, VarDecl};
+pub use self::directive::{Directive, GenericDirective, Import};
+pub use self::expression::{Expression, ExpressionKind, Operator};
+pub use self::block::{Block, Statement};
+pub use self::fn_literal::{FnLiteral, Param};
+pub use self::type_expr::{TypeExpr, TypeExprKind, StructField};
+pub use self::namespace::Namespace;
+pub use self::lit_integer::LitInteger;
+pub use self::lit_real::LitReal;
+pub use self::lit_string::LitString;
+pub use self::lit_codepoint::LitCodepoint;
+pub use self::literals::check_literals;
+pub use self::node_id::{NodeId, NodeMap};
+pub use self::precedence::{Associativity, Precedence, PrecedenceTable};
 
-#[derive(Eq, PartialEq, Copy, Clone, Debug)]
-pub enum ParseTree<'a> {
+/// The parse tree of a file.  A file with no declarations is `Empty`,
+/// and otherwise its declarations are the `Root` namespace.
+#[derive(Clone, Debug)]
+pub enum ParseTree {
     Empty,
-    Root(Namespace<'a>)
+    Root(Namespace)
+}
+
+impl ParseTree {
+    /// Whether the file has no declarations.
+    pub fn is_empty(&self) -> bool {
+        match *self {
+            ParseTree::Empty => true,
+            ParseTree::Root(_) => false,
+        }
+    }
+}
+
+
+/// Parses the tokens of a whole file, which is an implicit namespace.
+///
+/// Parsing carries on after errors, at the next line, so all of the
+/// declarations that parse are in the tree.  Compiler directives are
+/// skipped, since they're parsed with `parse_directives()`.
+pub fn parse_file<'s, 't>(tokens: &'s [Token<'t>]) -> (ParseTree, Vec<ParseError>) {
+    parse_file_with(tokens, &precedence::BUILTIN)
+}
+
+
+/// Parses the tokens of a whole file like `parse_file()`, but with the
+/// given operator precedences instead of the built-in ones.
+pub fn parse_file_with<'s, 't>(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> (ParseTree, Vec<ParseError>) {
+    let mut errors = Vec::new();
+    let (decls, rem) = namespace::parse_declarations(tokens, TokenKind::EOF, precedence, &mut errors);
+
+    // Anything but EOF left over is an error
+    match rem.get(0) {
+        None | Some(&Token { kind: TokenKind::EOF, .. }) => {}
+        Some(t) => errors.push(ParseError {
+            code: codes::UNEXPECTED_TOKEN,
+            message: "Expected end of file.".to_string(),
+            source: Some(t.span),
+            help: None,
+        }),
+    }
+
+    if decls.len() == 0 {
+        return (ParseTree::Empty, errors);
+    }
+    let source = decls[0].source().merge(decls[decls.len() - 1].source());
+    let root = Namespace {
+        id: NodeId::next(),
+        decls: decls,
+        source: source,
+    };
+    return (ParseTree::Root(root), errors);
+}
+
+
+/// Parses all of the compiler directives in a token stream.
+///
+/// Directives are the lines starting with a `TokenKind::Directive` token, and are
+/// set apart from the rest of the code before it's parsed any further.
+pub fn parse_directives<'s, 't>(tokens: &'s [Token<'t>]) -> (Vec<Directive<'s, 't>>, Vec<ParseError>) {
+    let mut directives = Vec::new();
+    let mut errors = Vec::new();
+    let mut remaining = tokens;
+    while remaining.len() > 0 {
+        if remaining[0].kind == TokenKind::Directive {
+            match Directive::parse(remaining) {
+                Ok((directive, rem)) => {
+                    directives.push(directive);
+                    remaining = rem;
+                }
+                Err(e) => {
+                    errors.push(e);
+                    remaining = &remaining[1..];
+                }
+            }
+        }
+        else {
+            remaining = &remaining[1..];
+        }
+    }
+    (directives, errors)
 }
 
 
 //=========================
 /// A trait for nodes in a parse tree that can be parsed from a token slice.
-pub trait Parseable<'a> {
-    fn parse(tokens: &'a [Token<'a>]) -> ParseResult<'a, Self>;
+pub trait Parseable<'s, 't> {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> where Self: Sized;
+
+    /// Parses with the given operator precedences.  Nodes that can
+    /// contain expressions override this, and parse with the built-in
+    /// precedences in `parse()`.
+    fn parse_with(tokens: &'s [Token<'t>], _precedence: &PrecedenceTable) -> ParseResult<'s, 't, Self> where Self: Sized {
+        Self::parse(tokens)
+    }
 }
 
 
 //=====================================
 // A parse error.
 #[derive(Clone, Debug)]
-pub struct ParseError<'a> {
+pub struct ParseError {
+    pub code: ErrorCode,
     pub message: String,
-    pub source: Option<SourceSpan<'a>>,
+    pub source: Option<Span>,
+    /// How the error might be fixed, if the parser has an idea.
+    pub help: Option<String>,
 }
 
-// Make it easy to print parse errors in a uniform way.
-impl<'a> Display for ParseError<'a> {
-    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        if let Some(ss) = self.source {
-            f.write_str(&format!("Parse Error [{}:{}]: {}", ss.line+1, ss.column, &self.message[..])[..])
+impl ParseError {
+    /// Turns the error into a diagnostic, for reporting.
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let mut diag = Diagnostic::error(&self.message[..]).with_code(self.code);
+        if let Some(ref help) = self.help {
+            diag = diag.with_help(help);
         }
-        else {
-            f.write_str(&format!("Parse Error: {}", &self.message[..])[..])
+        match self.source {
+            Some(ss) => diag.with_primary_label(ss, ""),
+            None => diag,
         }
     }
 }
 
 // Alias for a result using a ParseError
-pub type ParseResult<'a, T> = Result<(T, &'a[Token<'a>]), ParseError<'a>>;
\ No newline at end of file
+pub type ParseResult<'s, 't, T> = Result<(T, &'s [Token<'t>]), ParseError>;
+
+
+// Takes a token of the given kind from the start of the slice, or fails
+// with "Expected {what}.".
+fn expect_token<'s, 't>(tokens: &'s [Token<'t>], kind: TokenKind, what: &str) -> ParseResult<'s, 't, &'s Token<'t>> {
+    match tokens.get(0) {
+        Some(t) if t.kind == kind => Ok((t, &tokens[1..])),
+        t => Err(ParseError {
+            code: codes::UNEXPECTED_TOKEN,
+            message: format!("Expected {}.", what),
+            source: t.map(|t| t.span),
+            help: None,
+        }),
+    }
+}
+
+
+// Skips any newlines at the start of the slice.
+fn skip_newlines<'s, 't>(tokens: &'s [Token<'t>]) -> &'s [Token<'t>] {
+    let n = tokens.iter().take_while(|t| t.kind == TokenKind::NewLine).count();
+    &tokens[n..]
+}
+
+
+// Parses a comma-separated list of items up to and including the
+// closing token, e.g. "]".  The opening token has already been taken.
+// Newlines are allowed around the items, and the last item may have a
+// trailing comma.
+fn parse_list<'s, 't, T, F>(tokens: &'s [Token<'t>], close: TokenKind, parse_item: F) -> ParseResult<'s, 't, Vec<T>>
+    where F: Fn(&'s [Token<'t>]) -> ParseResult<'s, 't, T>
+{
+    let mut items = Vec::new();
+    let mut rem = skip_newlines(tokens);
+    while rem.get(0).map(|t| t.kind) != Some(close) {
+        let (item, r) = parse_item(rem)?;
+        items.push(item);
+        rem = skip_newlines(r);
+        match rem.get(0).map(|t| t.kind) {
+            Some(TokenKind::Comma) => rem = skip_newlines(&rem[1..]),
+            _ => break,
+        }
+    }
+    let close_text = format!("\",\" or \"{}\"", close.canonical_text().unwrap());
+    let (_, rem) = expect_token(rem, close, &close_text)?;
+    return Ok((items, rem));
+}
+
+
+// The span from the start of `start` to the end of the last token before
+// `rest`, where `rest` is a suffix of `start`.
+fn span_between<'s, 't>(start: &'s [Token<'t>], rest: &'s [Token<'t>]) -> Span {
+    let used = start.len() - rest.len();
+    start[0].span.merge(start[used - 1].span)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::lex_str;
+    use std::fs;
+    use std::io::Read;
+
+    fn parse(text: &str) -> (ParseTree, Vec<ParseError>) {
+        let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+        parse_file(&tokens[..])
+    }
+
+    #[test]
+    fn empty() {
+        assert!(parse("").0.is_empty());
+        assert!(parse("\n\n# Comment\n").0.is_empty());
+        assert!(parse("%import std.io\n").0.is_empty());
+    }
+
+    #[test]
+    fn declarations() {
+        let (tree, errors) = parse("%import std.io\n\n#: Doc\nval a = 1\n\nvar b\n");
+        assert_eq!(errors.len(), 0);
+        match tree {
+            ParseTree::Root(ns) => {
+                assert_eq!(ns.decls.len(), 2);
+                assert_eq!(ns.source.range(), 23..39);
+            }
+            ParseTree::Empty => panic!("Expected declarations"),
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let (tree, errors) = parse("val a = 1\nvar\nvar c\n)");
+        match tree {
+            ParseTree::Root(ns) => assert_eq!(ns.decls.len(), 2),
+            ParseTree::Empty => panic!("Expected declarations"),
+        }
+        let messages: Vec<_> = errors.iter().map(|e| &e.message[..]).collect();
+        assert_eq!(messages, vec!["Expected name after \"var\".", "Expected declaration."]);
+    }
+
+    #[test]
+    fn example_files() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/doc/examples/test.rune");
+        let mut text = String::new();
+        fs::File::open(path).unwrap().read_to_string(&mut text).unwrap();
+        let (tree, errors) = parse(&text);
+        assert_eq!(errors.len(), 0);
+        match tree {
+            ParseTree::Root(ns) => {
+                let names: Vec<_> = ns.decls.iter().map(|d| d.name().unwrap().as_str()).collect();
+                assert_eq!(names, vec!["c", "a", "foo", "Yar", "main"]);
+            }
+            ParseTree::Empty => panic!("Expected declarations"),
+        }
+    }
+}
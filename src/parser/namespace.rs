@@ -1,6 +1,206 @@
+use super::super::source_map::Span;
+use super::super::token::{Token, TokenKind};
+use super::{ParseResult, ParseError, Parseable, expect_token, span_between};
+use super::super::diagnostics::codes;
+
 use super::declaration::Declaration;
+use super::node_id::NodeId;
+use super::precedence::{self, PrecedenceTable};
+
+/// A namespace: a sequence of declarations, separated by newlines.  A file
+/// is an implicit namespace, and namespaces can be nested with
+/// `namespace ( ... )`.
+#[derive(Clone, Debug)]
+pub struct Namespace {
+    pub id: NodeId,
+    pub decls: Vec<Declaration>,
+    pub source: Span,
+}
+
+
+//=============================
+impl<'s, 't> Parseable<'s, 't> for Namespace {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        Namespace::parse_with(tokens, &precedence::BUILTIN)
+    }
+
+
+    // Fails with the first error in the namespace.  Use
+    // `parse_namespace()` to get all of them.
+    fn parse_with(tokens: &'s [Token<'t>], precedence: &PrecedenceTable) -> ParseResult<'s, 't, Self> {
+        let mut errors = Vec::new();
+        let (ns, rem) = parse_namespace(tokens, precedence, &mut errors)?;
+        match errors.into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok((ns, rem)),
+        }
+    }
+}
+
+
+/// Parses a nested namespace, e.g. `namespace ( ... )`.
+///
+/// Only fails if the tokens don't start a namespace.  Errors inside it are
+/// added to `errors`, as with `parse_declarations()`, and the declarations
+/// that parse are kept.
+pub fn parse_namespace<'s, 't>(tokens: &'s [Token<'t>], precedence: &PrecedenceTable, errors: &mut Vec<ParseError>)
+    -> ParseResult<'s, 't, Namespace>
+{
+    let (_, rem) = expect_token(tokens, TokenKind::KEY_Namespace, "\"namespace\"")?;
+    let (_, rem) = expect_token(rem, TokenKind::LParen, "\"(\" after \"namespace\"")?;
+    let (decls, rem) = parse_declarations(rem, TokenKind::RParen, precedence, errors);
+    let rem = match expect_token(rem, TokenKind::RParen, "\")\"") {
+        Ok((_, rem)) => rem,
+        Err(e) => {
+            errors.push(e);
+            rem
+        }
+    };
+    return Ok((
+        Namespace {
+            id: NodeId::next(),
+            decls: decls,
+            source: span_between(tokens, rem),
+        },
+        rem,
+    ));
+}
+
+
+/// Parses declarations separated by newlines, up to but not including a
+/// token of the kind `end`, or EOF.
+///
+/// Errors are added to `errors`, and parsing carries on at the next line
+/// after a declaration that fails to parse.  Doc comments and compiler
+/// directives are skipped: they're handled separately.
+pub fn parse_declarations<'s, 't>(tokens: &'s [Token<'t>], end: TokenKind, precedence: &PrecedenceTable, errors: &mut Vec<ParseError>)
+    -> (Vec<Declaration>, &'s [Token<'t>])
+{
+    let mut decls = Vec::new();
+    let mut rem = tokens;
+    loop {
+        match rem.get(0).map(|t| t.kind) {
+            None | Some(TokenKind::EOF) => break,
+            Some(kind) if kind == end => break,
+            Some(TokenKind::NewLine) | Some(TokenKind::DocComment) => {
+                rem = &rem[1..];
+                continue;
+            }
+            Some(TokenKind::Directive) => {
+                rem = skip_line(rem);
+                continue;
+            }
+            _ => {}
+        }
+
+        // Nested namespaces report their own errors, and keep what parses
+        let result = if rem[0].kind == TokenKind::KEY_Namespace {
+            parse_namespace(rem, precedence, errors).map(|(ns, r)| (Declaration::Namespace(ns), r))
+        }
+        else {
+            Declaration::parse_with(rem, precedence)
+        };
+        match result {
+            Ok((decl, r)) => {
+                decls.push(decl);
+                rem = r;
+
+                // Each declaration ends the line
+                match rem.get(0).map(|t| t.kind) {
+                    None | Some(TokenKind::NewLine) | Some(TokenKind::EOF) => {}
+                    Some(kind) if kind == end => {}
+                    _ => {
+                        errors.push(ParseError {
+                            code: codes::UNEXPECTED_TOKEN,
+                            message: "Expected newline after declaration.".to_string(),
+                            source: rem.get(0).map(|t| t.span),
+                            help: None,
+                        });
+                        rem = skip_line(rem);
+                    }
+                }
+            }
+            Err(e) => {
+                errors.push(e);
+                rem = skip_line(rem);
+            }
+        }
+    }
+    return (decls, rem);
+}
+
+
+// Skips to the next newline that isn't nested in brackets, or to EOF.
+fn skip_line<'s, 't>(tokens: &'s [Token<'t>]) -> &'s [Token<'t>] {
+    let mut depth = 0;
+    for (i, t) in tokens.iter().enumerate() {
+        match t.kind {
+            TokenKind::LParen | TokenKind::LSquare | TokenKind::LCurly => depth += 1,
+            TokenKind::RParen | TokenKind::RSquare | TokenKind::RCurly if depth > 0 => depth -= 1,
+            TokenKind::NewLine if depth == 0 => return &tokens[i..],
+            TokenKind::EOF => return &tokens[i..],
+            _ => {}
+        }
+    }
+    return &tokens[tokens.len()..];
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::lex_str;
+
+    #[test]
+    fn nested() {
+        let tokens: Vec<_> = lex_str("namespace (\n\tval a = 1\n\n\ttype B: i32\n)").0.into_iter().collect();
+        let (ns, rem) = Namespace::parse(&tokens[..]).unwrap();
+        assert_eq!(rem[0].kind, TokenKind::EOF);
+        assert_eq!(ns.decls.len(), 2);
+        assert_eq!(ns.source.range(), 0..38);
+    }
+
+    #[test]
+    fn recovery() {
+        let tokens: Vec<_> = lex_str("val a = (1\n2)\nval = 3\nval b = foo[\n\t1,\n]  , x\nvar c").0.into_iter().collect();
+        let mut errors = Vec::new();
+        let (decls, rem) = parse_declarations(&tokens[..], TokenKind::EOF, &precedence::BUILTIN, &mut errors);
+        assert_eq!(rem[0].kind, TokenKind::EOF);
+        let names: Vec<_> = decls.iter().map(|d| d.name().unwrap().as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+        let messages: Vec<_> = errors.iter().map(|e| &e.message[..]).collect();
+        assert_eq!(messages, vec!["Expected name after \"val\".", "Expected newline after declaration."]);
+    }
+
+    #[test]
+    fn nested_recovery() {
+        let tokens: Vec<_> = lex_str("namespace (\n\tval x\n\tval y\n\tval z = 1\n)\nval w = 2\n").0.into_iter().collect();
+        let mut errors = Vec::new();
+        let (decls, rem) = parse_declarations(&tokens[..], TokenKind::EOF, &precedence::BUILTIN, &mut errors);
+        assert_eq!(rem[0].kind, TokenKind::EOF);
+        let sources: Vec<_> = errors.iter().map(|e| e.source.unwrap().range()).collect();
+        assert_eq!(sources, vec![13..18, 20..25]);
+        assert_eq!(decls.len(), 2);
+        match decls[0] {
+            Declaration::Namespace(ref ns) => {
+                let names: Vec<_> = ns.decls.iter().map(|d| d.name().unwrap().as_str()).collect();
+                assert_eq!(names, vec!["z"]);
+            }
+            ref d => panic!("Expected a namespace, got {:?}", d),
+        }
+
+        // Parsed on its own, a namespace fails with its first error
+        let e = Namespace::parse(&tokens[..]).unwrap_err();
+        assert_eq!(e.source.unwrap().range(), 13..18);
 
-#[derive(Eq, PartialEq, Copy, Clone, Debug)]
-pub struct Namespace<'a> {
-    decls: &'a [Declaration<'a>],
-}
\ No newline at end of file
+        // An unclosed namespace still has its declarations
+        let tokens: Vec<_> = lex_str("namespace (\n\tval z = 1\n").0.into_iter().collect();
+        let mut errors = Vec::new();
+        let (decls, _) = parse_declarations(&tokens[..], TokenKind::EOF, &precedence::BUILTIN, &mut errors);
+        assert_eq!(errors[0].message, "Expected \")\".");
+        match decls[0] {
+            Declaration::Namespace(ref ns) => assert_eq!(ns.decls.len(), 1),
+            ref d => panic!("Expected a namespace, got {:?}", d),
+        }
+    }
+}
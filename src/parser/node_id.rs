@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Identifies a node of a parse tree, so that later passes can keep
+/// information about nodes, such as their types, in side tables (see
+/// `NodeMap`).
+///
+/// Ids come from a global counter, like symbols come from a global
+/// interner, so every node parsed by the process gets a different id,
+/// even across files.  Parsing the same source twice gives different ids,
+/// so nodes don't implement `PartialEq`: equality would compare the ids.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Debug)]
+pub struct NodeId(u32);
+
+impl NodeId {
+    /// Returns a new id, different from all others.
+    pub fn next() -> NodeId {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        assert!(id <= u32::MAX as usize, "Ran out of node ids.");
+        NodeId(id as u32)
+    }
+
+
+    /// Returns the id's number.
+    pub fn as_u32(&self) -> u32 {
+        self.0
+    }
+}
+
+
+/// A side table of information about nodes.
+pub type NodeMap<T> = HashMap<NodeId, T>;
+
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique() {
+        let a = NodeId::next();
+        let b = NodeId::next();
+        assert!(a != b);
+        assert!(b > a);
+    }
+}
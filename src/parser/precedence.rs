@@ -0,0 +1,153 @@
+//! Operator precedence, for parsing infix expressions.
+//!
+//! Prefix and postfix operators always bind tighter than infix operators,
+//! so only infix operators have entries in the table.  For example, with
+//! the built-in table:
+//!
+//! ```text
+//! a = b + c * -d    # a = (b + (c * (-d)))
+//! a - b - c         # (a - b) - c
+//! a = b = c         # a = (b = c)
+//! ```
+
+use std::collections::HashMap;
+
+/// Which way a chain of operators with the same precedence groups.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`.
+    Left,
+
+    /// `a = b = c` is `a = (b = c)`.
+    Right,
+}
+
+
+/// How tightly an infix operator binds.  Operators with higher levels
+/// bind tighter.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct Precedence {
+    pub level: u32,
+    pub associativity: Associativity,
+}
+
+
+/// A table of infix operator precedences.  Operators that aren't in the
+/// table, such as operators declared by the source, get the table's
+/// default precedence.
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct PrecedenceTable {
+    operators: HashMap<String, Precedence>,
+    default: Precedence,
+}
+
+impl PrecedenceTable {
+    /// Creates a table with no operators, where every operator gets
+    /// `default`.
+    pub fn new(default: Precedence) -> PrecedenceTable {
+        PrecedenceTable {
+            operators: HashMap::new(),
+            default: default,
+        }
+    }
+
+
+    /// Creates a table of the built-in operators' precedences.  From
+    /// loosest to tightest:
+    ///
+    /// ```text
+    /// = += -= *= /= %= &= |= <<= >>=    right
+    /// ||                                left
+    /// &&                                left
+    /// == != < > <= >=                   left
+    /// (operators not in the table)      left
+    /// |                                 left
+    /// &                                 left
+    /// << >>                             left
+    /// + -                               left
+    /// * / %                             left
+    /// ```
+    pub fn builtin() -> PrecedenceTable {
+        let mut table = PrecedenceTable::new(Precedence {
+            level: DEFAULT_LEVEL,
+            associativity: Associativity::Left,
+        });
+        for &(ops, level, associativity) in BUILTIN_PRECEDENCES {
+            for op in ops {
+                table.set(op, level, associativity);
+            }
+        }
+        table
+    }
+
+
+    /// Sets the precedence of an operator, replacing any it had before.
+    pub fn set(&mut self, op: &str, level: u32, associativity: Associativity) {
+        self.operators.insert(op.to_string(), Precedence {
+            level: level,
+            associativity: associativity,
+        });
+    }
+
+
+    /// Returns the precedence of an operator.
+    pub fn get(&self, op: &str) -> Precedence {
+        match self.operators.get(op) {
+            Some(&precedence) => precedence,
+            None => self.default,
+        }
+    }
+}
+
+
+lazy_static! {
+    /// The built-in table, used when no other table is given.
+    pub static ref BUILTIN: PrecedenceTable = PrecedenceTable::builtin();
+}
+
+
+const DEFAULT_LEVEL: u32 = 5;
+
+const BUILTIN_PRECEDENCES: &'static [(&'static [&'static str], u32, Associativity)] = &[
+    (&["=", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "<<=", ">>="], 1, Associativity::Right),
+    (&["||"], 2, Associativity::Left),
+    (&["&&"], 3, Associativity::Left),
+    (&["==", "!=", "<", ">", "<=", ">="], 4, Associativity::Left),
+    (&["|"], 6, Associativity::Left),
+    (&["&"], 7, Associativity::Left),
+    (&["<<", ">>"], 8, Associativity::Left),
+    (&["+", "-"], 9, Associativity::Left),
+    (&["*", "/", "%"], 10, Associativity::Left),
+];
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin() {
+        let table = PrecedenceTable::builtin();
+        assert!(table.get("*").level > table.get("+").level);
+        assert!(table.get("+").level > table.get("==").level);
+        assert!(table.get("==").level > table.get("&&").level);
+        assert!(table.get("&&").level > table.get("||").level);
+        assert!(table.get("||").level > table.get("=").level);
+        assert_eq!(table.get("=").associativity, Associativity::Right);
+        assert_eq!(table.get("-").associativity, Associativity::Left);
+
+        // Operators not in the table
+        assert_eq!(table.get("<+>").level, DEFAULT_LEVEL);
+        assert!(table.get("<+>").level < table.get("+").level);
+        assert!(table.get("<+>").level > table.get("==").level);
+    }
+
+    #[test]
+    fn set() {
+        let mut table = PrecedenceTable::builtin();
+        table.set("<+>", 9, Associativity::Right);
+        assert_eq!(table.get("<+>"), Precedence { level: 9, associativity: Associativity::Right });
+        table.set("+", 1, Associativity::Left);
+        assert_eq!(table.get("+").level, 1);
+    }
+}
@@ -0,0 +1,231 @@
+use super::super::source_map::Span;
+use super::super::symbol::Symbol;
+use super::super::token::{Token, TokenKind};
+use super::{ParseResult, ParseError, Parseable, expect_token, parse_list, span_between};
+use super::super::diagnostics::codes;
+
+use super::expression::{Expression, ExpressionKind};
+use super::lit_integer::LitInteger;
+use super::node_id::NodeId;
+
+/// A type, as written in a type ascription, e.g. the `@i32` of
+/// `val a: @i32`.
+#[derive(Clone, Debug)]
+pub struct TypeExpr {
+    pub id: NodeId,
+    pub kind: TypeExprKind,
+    pub source: Span,
+}
+
+
+/// The kinds of type expressions.
+#[derive(Clone, Debug)]
+pub enum TypeExprKind {
+    /// A named type, e.g. `i32` or `Meters`.
+    Named(Symbol),
+
+    /// A pointer, e.g. `@T`.
+    Pointer(Box<TypeExpr>),
+
+    /// A slice, e.g. `[]T`.
+    Slice(Box<TypeExpr>),
+
+    /// An array, e.g. `[4]T`.  The length must be an integer literal.
+    Array { length: Box<Expression>, element: Box<TypeExpr> },
+
+    /// A tuple, e.g. `{i32, f64}`.
+    Tuple(Vec<TypeExpr>),
+
+    /// A struct, e.g. `struct { x: i32, y: i32 }`.
+    Struct(Vec<StructField>),
+}
+
+impl TypeExpr {
+    pub fn new(kind: TypeExprKind, source: Span) -> TypeExpr {
+        TypeExpr {
+            id: NodeId::next(),
+            kind: kind,
+            source: source,
+        }
+    }
+}
+
+
+/// A field of a struct type, e.g. `x: i32`.
+#[derive(Clone, Debug)]
+pub struct StructField {
+    pub id: NodeId,
+    pub name: Symbol,
+    pub ty: TypeExpr,
+    pub source: Span,
+}
+
+
+//=============================
+impl<'s, 't> Parseable<'s, 't> for TypeExpr {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        match tokens.get(0).map(|t| t.kind) {
+            // Named type
+            Some(TokenKind::Identifier) => {
+                let t = &tokens[0];
+                return Ok((
+                    TypeExpr::new(TypeExprKind::Named(t.symbol().unwrap()), t.span),
+                    &tokens[1..],
+                ));
+            }
+
+            // Pointer
+            Some(TokenKind::At) => {
+                let (target, rem) = TypeExpr::parse(&tokens[1..])?;
+                return Ok((
+                    TypeExpr::new(TypeExprKind::Pointer(Box::new(target)), span_between(tokens, rem)),
+                    rem,
+                ));
+            }
+
+            // Slice or array
+            Some(TokenKind::LSquare) => {
+                let rem = &tokens[1..];
+                let (length, rem) = if rem.get(0).map(|t| t.kind) == Some(TokenKind::LIT_Int) {
+                    let (lit, r) = LitInteger::parse(rem)?;
+                    let source = lit.source;
+                    (Some(Expression::new(ExpressionKind::LitInteger(lit), source)), r)
+                }
+                else {
+                    (None, rem)
+                };
+                let (_, rem) = expect_token(rem, TokenKind::RSquare, "\"]\"")?;
+                let (element, rem) = TypeExpr::parse(rem)?;
+                let element = Box::new(element);
+                let kind = match length {
+                    Some(length) => TypeExprKind::Array { length: Box::new(length), element: element },
+                    None => TypeExprKind::Slice(element),
+                };
+                return Ok((TypeExpr::new(kind, span_between(tokens, rem)), rem));
+            }
+
+            // Tuple
+            Some(TokenKind::LCurly) => {
+                let (elements, rem) = parse_list(&tokens[1..], TokenKind::RCurly, TypeExpr::parse)?;
+                return Ok((
+                    TypeExpr::new(TypeExprKind::Tuple(elements), span_between(tokens, rem)),
+                    rem,
+                ));
+            }
+
+            // Struct
+            Some(TokenKind::KEY_Struct) => {
+                let (_, rem) = expect_token(&tokens[1..], TokenKind::LCurly, "\"{\" after \"struct\"")?;
+                let (fields, rem) = parse_list(rem, TokenKind::RCurly, StructField::parse)?;
+                return Ok((
+                    TypeExpr::new(TypeExprKind::Struct(fields), span_between(tokens, rem)),
+                    rem,
+                ));
+            }
+
+            _ => {
+                return Err(ParseError {
+                    code: codes::UNEXPECTED_TOKEN,
+                    message: "Expected type.".to_string(),
+                    source: tokens.get(0).map(|t| t.span),
+                    help: None,
+                });
+            }
+        }
+    }
+}
+
+
+impl<'s, 't> Parseable<'s, 't> for StructField {
+    fn parse(tokens: &'s [Token<'t>]) -> ParseResult<'s, 't, Self> {
+        let (name, rem) = expect_token(tokens, TokenKind::Identifier, "field name")?;
+        let (_, rem) = expect_token(rem, TokenKind::Colon, "\":\" after field name")?;
+        let (ty, rem) = TypeExpr::parse(rem)?;
+        return Ok((
+            StructField {
+                id: NodeId::next(),
+                name: name.symbol().unwrap(),
+                ty: ty,
+                source: span_between(tokens, rem),
+            },
+            rem,
+        ));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::lex_str;
+
+    fn parse(text: &str) -> Result<TypeExpr, String> {
+        let tokens: Vec<_> = lex_str(text).0.into_iter().collect();
+        match TypeExpr::parse(&tokens[..]) {
+            Ok((ty, rem)) => {
+                assert_eq!(rem[0].kind, TokenKind::EOF);
+                Ok(ty)
+            }
+            Err(e) => Err(e.message),
+        }
+    }
+
+    // Describes a type expression compactly, for comparing in tests.
+    fn describe(ty: &TypeExpr) -> String {
+        match ty.kind {
+            TypeExprKind::Named(name) => name.as_str().to_string(),
+            TypeExprKind::Pointer(ref target) => format!("@{}", describe(target)),
+            TypeExprKind::Slice(ref element) => format!("[]{}", describe(element)),
+            TypeExprKind::Array { ref length, ref element } => match length.kind {
+                ExpressionKind::LitInteger(ref lit) => format!("[{}]{}", lit.text, describe(element)),
+                ref k => panic!("Expected an integer literal, got {:?}", k),
+            },
+            TypeExprKind::Tuple(ref elements) => {
+                let elements: Vec<_> = elements.iter().map(describe).collect();
+                format!("{{{}}}", elements.join(", "))
+            }
+            TypeExprKind::Struct(ref fields) => {
+                let fields: Vec<_> = fields.iter().map(|f| format!("{}: {}", f.name.as_str(), describe(&f.ty))).collect();
+                format!("struct {{{}}}", fields.join(", "))
+            }
+        }
+    }
+
+    #[test]
+    fn types() {
+        assert_eq!(describe(&parse("i32").unwrap()), "i32");
+        assert_eq!(describe(&parse("@@Foo").unwrap()), "@@Foo");
+        assert_eq!(describe(&parse("[]u8").unwrap()), "[]u8");
+        assert_eq!(describe(&parse("[4]@f32").unwrap()), "[4]@f32");
+        assert_eq!(describe(&parse("{i32, {}, f64}").unwrap()), "{i32, {}, f64}");
+        assert_eq!(describe(&parse("struct {\n\tx: i32,\n\ty: []i32,\n}").unwrap()), "struct {x: i32, y: []i32}");
+    }
+
+    #[test]
+    fn source() {
+        let ty = parse("  @[4]i32").unwrap();
+        assert_eq!(ty.source.range(), 2..9);
+        match ty.kind {
+            TypeExprKind::Pointer(ref target) => {
+                assert_eq!(target.source.range(), 3..9);
+                assert!(target.id != ty.id);
+                match target.kind {
+                    TypeExprKind::Array { ref length, .. } => {
+                        assert_eq!(length.source.range(), 4..5);
+                        assert!(length.id != target.id);
+                    }
+                    ref k => panic!("Expected an array, got {:?}", k),
+                }
+            }
+            ref k => panic!("Expected a pointer, got {:?}", k),
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(parse("5").unwrap_err(), "Expected type.");
+        assert_eq!(parse("[4 i32").unwrap_err(), "Expected \"]\".");
+        assert_eq!(parse("{i32 f64}").unwrap_err(), "Expected \",\" or \"}\".");
+        assert_eq!(parse("struct {x}").unwrap_err(), "Expected \":\" after field name.");
+    }
+}
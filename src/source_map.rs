@@ -0,0 +1,382 @@
+#![allow(dead_code)]
+
+use std::convert::TryFrom;
+use std::fmt::{Debug, Formatter, Error};
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Identifies a file in a `SourceMap`.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Debug)]
+pub struct FileId(pub u32);
+
+
+/// A span of source code: a range of bytes in a file.
+///
+/// Spans are small and `Copy`, and are included in tokens and nodes
+/// throughout the lexing and parsing process for a variety of purposes,
+/// including error messages.  The text, file name, line, and column of a
+/// span are looked up in the `SourceMap` that its file belongs to.
+#[derive(Eq, PartialEq, Hash, Copy, Clone)]
+pub struct Span {
+    pub file_id: FileId,
+    pub lo: u32,
+    pub hi: u32,
+}
+
+impl Span {
+    /// Creates a span of the bytes from `lo` to `hi` of a file.  Offsets
+    /// must fit in a `u32`, which `SourceMap::add_file()` ensures.
+    pub fn new(file_id: FileId, lo: usize, hi: usize) -> Span {
+        Span {
+            file_id: file_id,
+            lo: u32::try_from(lo).expect("Span offset doesn't fit in a u32."),
+            hi: u32::try_from(hi).expect("Span offset doesn't fit in a u32."),
+        }
+    }
+
+
+    /// The length of the span in bytes.
+    pub fn len(&self) -> usize {
+        (self.hi - self.lo) as usize
+    }
+
+
+    pub fn is_empty(&self) -> bool {
+        self.hi == self.lo
+    }
+
+
+    /// The byte range of the span, for slicing its file's text.
+    pub fn range(&self) -> Range<usize> {
+        (self.lo as usize)..(self.hi as usize)
+    }
+
+
+    /// Returns the smallest span that covers both spans.  The spans must
+    /// be from the same file.
+    pub fn merge(&self, other: Span) -> Span {
+        assert_eq!(self.file_id, other.file_id, "Can't merge spans from different files.");
+        Span {
+            file_id: self.file_id,
+            lo: if self.lo < other.lo { self.lo } else { other.lo },
+            hi: if self.hi > other.hi { self.hi } else { other.hi },
+        }
+    }
+
+
+    /// Returns the span of the bytes between `start` and `end` of this
+    /// span.
+    pub fn sub_span(&self, start: usize, end: usize) -> Span {
+        Span {
+            file_id: self.file_id,
+            lo: self.lo + start as u32,
+            hi: self.lo + end as u32,
+        }
+    }
+}
+
+
+// Spans are in every node of a parse tree, so they're kept short when
+// debug printed, e.g. "Span(0:8..13)".
+impl Debug for Span {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "Span({}:{}..{})", self.file_id.0, self.lo, self.hi)
+    }
+}
+
+
+/// A zero-based line and column.  The column is counted in grapheme
+/// clusters, which is what a user perceives as characters.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct LineCol {
+    pub line: u32,
+    pub column: u32,
+}
+
+
+//=====================================
+/// A loaded source file.
+pub struct SourceFile {
+    id: FileId,
+    name: String,
+    text: String,
+    // Byte offsets of the start of each line
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn id(&self) -> FileId {
+        self.id
+    }
+
+
+    pub fn name(&self) -> &str {
+        &self.name[..]
+    }
+
+
+    pub fn text(&self) -> &str {
+        &self.text[..]
+    }
+
+
+    /// The number of lines in the file.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+
+    /// The zero-based line that byte `offset` is on.  "\n", "\r\n", and
+    /// "\r" all end a line.
+    pub fn line_index(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        }
+    }
+
+
+    /// The byte offset of the start of a zero-based line.
+    pub fn line_start(&self, line: usize) -> usize {
+        self.line_starts[line]
+    }
+
+
+    /// The text of a zero-based line, without its line ending.
+    pub fn line_text(&self, line: usize) -> &str {
+        let start = self.line_starts[line];
+        let end = if line + 1 < self.line_starts.len() { self.line_starts[line + 1] } else { self.text.len() };
+        self.text[start..end].trim_end_matches(['\n', '\r'])
+    }
+
+
+    /// The line and column of byte `offset`.
+    pub fn line_col(&self, offset: usize) -> LineCol {
+        LineCol {
+            line: self.line_index(offset) as u32,
+            column: self.grapheme_column(offset),
+        }
+    }
+
+
+    /// The column of byte `offset`, in bytes.
+    pub fn byte_column(&self, offset: usize) -> u32 {
+        self.line_prefix(offset).len() as u32
+    }
+
+
+    /// The column of byte `offset`, in Unicode scalar values.
+    pub fn char_column(&self, offset: usize) -> u32 {
+        self.line_prefix(offset).chars().count() as u32
+    }
+
+
+    /// The column of byte `offset`, in extended grapheme clusters.
+    pub fn grapheme_column(&self, offset: usize) -> u32 {
+        let prefix = self.line_prefix(offset);
+        if prefix.is_ascii() {
+            // Fast path.  The only multi-character grapheme cluster in
+            // ASCII is "\r\n", which never shows up in the middle of a line.
+            return prefix.len() as u32;
+        }
+        prefix.graphemes(true).count() as u32
+    }
+
+
+    /// The column of byte `offset`, in UTF-16 code units.  This is what
+    /// e.g. the Language Server Protocol uses.
+    pub fn utf16_column(&self, offset: usize) -> u32 {
+        self.line_prefix(offset).chars().map(|c| c.len_utf16() as u32).sum()
+    }
+
+
+    // The text from the start of the line up to byte `offset`.
+    fn line_prefix(&self, offset: usize) -> &str {
+        &self.text[self.line_starts[self.line_index(offset)]..offset]
+    }
+}
+
+
+// Finds the byte offsets of the start of each line.
+fn line_starts(text: &str) -> Vec<usize> {
+    let bytes = text.as_bytes();
+    let mut starts = vec![0];
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\r' && i + 1 < bytes.len() && bytes[i + 1] == b'\n' {
+            i += 1;
+        }
+        if bytes[i] == b'\r' || bytes[i] == b'\n' {
+            starts.push(i + 1);
+        }
+        i += 1;
+    }
+    starts
+}
+
+
+//=====================================
+/// Owns all of the loaded source files, and looks up information about
+/// spans in them.
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl Default for SourceMap {
+    fn default() -> SourceMap {
+        SourceMap::new()
+    }
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap {
+            files: Vec::new(),
+        }
+    }
+
+
+    /// Adds a file, returning its id.  The first file added gets id 0,
+    /// the next 1, and so on.
+    ///
+    /// Spans store byte offsets as `u32`s, so files must be smaller than
+    /// 4 GiB.  Panics if the file is larger.
+    pub fn add_file(&mut self, name: &str, text: String) -> FileId {
+        assert!(u32::try_from(text.len()).is_ok(), "\"{}\" is too large: files must be smaller than 4 GiB.", name);
+        let id = FileId(self.files.len() as u32);
+        self.files.push(SourceFile {
+            id: id,
+            name: name.to_string(),
+            line_starts: line_starts(&text[..]),
+            text: text,
+        });
+        id
+    }
+
+
+    pub fn file(&self, id: FileId) -> &SourceFile {
+        &self.files[id.0 as usize]
+    }
+
+
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files[..]
+    }
+
+
+    /// The source text of a span.
+    pub fn text(&self, span: Span) -> &str {
+        &self.file(span.file_id).text()[span.range()]
+    }
+
+
+    /// The name of the file a span is in.
+    pub fn file_name(&self, span: Span) -> &str {
+        self.file(span.file_id).name()
+    }
+
+
+    /// The line and column of the start of a span.
+    pub fn line_col(&self, span: Span) -> LineCol {
+        self.file(span.file_id).line_col(span.lo as usize)
+    }
+
+
+    /// The text of the line that a span starts on, without its line
+    /// ending.
+    pub fn line_text(&self, span: Span) -> &str {
+        let file = self.file(span.file_id);
+        file.line_text(file.line_index(span.lo as usize))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge() {
+        let a = Span::new(FileId(0), 0, 22);
+        let b = Span::new(FileId(0), 23, 32);
+        assert_eq!(a.merge(b), Span::new(FileId(0), 0, 32));
+        assert_eq!(b.merge(a), Span::new(FileId(0), 0, 32));
+    }
+
+    #[test]
+    #[should_panic]
+    fn merge_different_files() {
+        Span::new(FileId(0), 0, 1).merge(Span::new(FileId(1), 0, 1));
+    }
+
+    #[test]
+    fn sub_span() {
+        let span = Span::new(FileId(2), 8, 20);
+        assert_eq!(span.sub_span(1, 3), Span::new(FileId(2), 9, 11));
+    }
+
+    #[test]
+    fn multiple_files() {
+        let mut map = SourceMap::new();
+        let a = map.add_file("a.rune", "val a = 1\n".to_string());
+        let b = map.add_file("b.rune", "var b\nvar c".to_string());
+        assert_eq!(a, FileId(0));
+        assert_eq!(b, FileId(1));
+
+        let span = Span::new(b, 10, 11);
+        assert_eq!(map.text(span), "c");
+        assert_eq!(map.file_name(span), "b.rune");
+        assert_eq!(map.line_col(span), LineCol { line: 1, column: 4 });
+        assert_eq!(map.line_text(span), "var c");
+        assert_eq!(map.text(Span::new(a, 4, 5)), "a");
+    }
+
+    #[test]
+    fn lines() {
+        let mut map = SourceMap::new();
+        let id = map.add_file("test.rune", "a\r\nb\rc\n\nd".to_string());
+        let file = map.file(id);
+        assert_eq!(file.line_count(), 5);
+        assert_eq!(file.line_text(0), "a");
+        assert_eq!(file.line_text(1), "b");
+        assert_eq!(file.line_text(2), "c");
+        assert_eq!(file.line_text(3), "");
+        assert_eq!(file.line_text(4), "d");
+        assert_eq!(file.line_index(1), 0);
+        assert_eq!(file.line_index(2), 0);
+        assert_eq!(file.line_index(3), 1);
+        assert_eq!(file.line_col(8), LineCol { line: 4, column: 0 });
+
+        // The end of the file is on the last line
+        assert_eq!(file.line_col(9), LineCol { line: 4, column: 1 });
+    }
+
+    #[test]
+    fn columns_ascii() {
+        let mut map = SourceMap::new();
+        let id = map.add_file("test.rune", "Hello\nthere".to_string());
+        let file = map.file(id);
+
+        assert_eq!(file.byte_column(8), 2);
+        assert_eq!(file.char_column(8), 2);
+        assert_eq!(file.grapheme_column(8), 2);
+        assert_eq!(file.utf16_column(8), 2);
+    }
+
+    #[test]
+    fn columns_non_ascii() {
+        // "e" followed by a combining acute accent is one grapheme made of
+        // two scalar values, and the emoji takes two UTF-16 code units.
+        let text = "a\r\nxe\u{301}😀速 größe";
+        let offset = text.find("größe").unwrap();
+        let mut map = SourceMap::new();
+        let id = map.add_file("test.rune", text.to_string());
+        let file = map.file(id);
+
+        assert_eq!(file.byte_column(offset), 1 + 3 + 4 + 3 + 1);
+        assert_eq!(file.char_column(offset), 6);
+        assert_eq!(file.grapheme_column(offset), 5);
+        assert_eq!(file.utf16_column(offset), 7);
+        assert_eq!(file.line_col(offset), LineCol { line: 1, column: 5 });
+    }
+}
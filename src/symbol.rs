@@ -0,0 +1,204 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter, Error};
+use std::sync::Mutex;
+
+/// An interned string, such as the name of an identifier.
+///
+/// Symbols are just an id into a global interner, so they are cheap to
+/// copy, compare, and hash.  The same text always gets the same symbol,
+/// no matter which file it came from.  Identifiers are interned in their
+/// normalized form (see `lexer::normalize_identifier()`).
+///
+/// Keywords are interned ahead of time, and have fixed symbols that are
+/// available as constants in the `kw` module.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Interns `text`, returning its symbol.
+    pub fn intern(text: &str) -> Symbol {
+        INTERNER.lock().unwrap().intern(text)
+    }
+
+
+    /// Returns the text of the symbol.
+    pub fn as_str(&self) -> &'static str {
+        INTERNER.lock().unwrap().get(*self)
+    }
+
+
+    /// Returns the symbol's id.
+    pub fn as_u32(&self) -> u32 {
+        self.0
+    }
+
+
+    /// Whether the symbol is a keyword.
+    pub fn is_keyword(&self) -> bool {
+        (self.0 as usize) < KEYWORDS.len()
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Debug for Symbol {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "Symbol({}, {:?})", self.0, self.as_str())
+    }
+}
+
+
+/// The keywords, in the order of their symbols.
+const KEYWORDS: &'static [&'static str] = &[
+    "namespace", "pub", "unsafe",
+    "const", "val", "var",
+    "mut", "ref",
+    "fn",
+    "struct", "enum", "union",
+    "trait", "is",
+    "if", "else", "loop", "while", "until", "for", "in", "break", "continue", "return",
+    "as",
+    "alias", "type",
+];
+
+/// The symbols of the keywords.
+pub mod kw {
+    use super::Symbol;
+
+    pub const NAMESPACE: Symbol = Symbol(0);
+    pub const PUB: Symbol = Symbol(1);
+    pub const UNSAFE: Symbol = Symbol(2);
+
+    pub const CONST: Symbol = Symbol(3);
+    pub const VAL: Symbol = Symbol(4);
+    pub const VAR: Symbol = Symbol(5);
+
+    pub const MUT: Symbol = Symbol(6);
+    pub const REF: Symbol = Symbol(7);
+
+    pub const FN: Symbol = Symbol(8);
+
+    pub const STRUCT: Symbol = Symbol(9);
+    pub const ENUM: Symbol = Symbol(10);
+    pub const UNION: Symbol = Symbol(11);
+
+    pub const TRAIT: Symbol = Symbol(12);
+    pub const IS: Symbol = Symbol(13);
+
+    pub const IF: Symbol = Symbol(14);
+    pub const ELSE: Symbol = Symbol(15);
+    pub const LOOP: Symbol = Symbol(16);
+    pub const WHILE: Symbol = Symbol(17);
+    pub const UNTIL: Symbol = Symbol(18);
+    pub const FOR: Symbol = Symbol(19);
+    pub const IN: Symbol = Symbol(20);
+    pub const BREAK: Symbol = Symbol(21);
+    pub const CONTINUE: Symbol = Symbol(22);
+    pub const RETURN: Symbol = Symbol(23);
+
+    pub const AS: Symbol = Symbol(24);
+
+    pub const ALIAS: Symbol = Symbol(25);
+    pub const TYPE: Symbol = Symbol(26);
+}
+
+
+lazy_static! {
+    static ref INTERNER: Mutex<Interner> = Mutex::new(Interner::new());
+}
+
+
+//=====================================
+// A string interner.
+//
+// Interned strings are never freed, which is what allows symbols to hand
+// out `&'static str`s for their text.  Symbols don't record which interner
+// they came from, so the global one is the only one outside of tests.
+struct Interner {
+    symbols: HashMap<&'static str, Symbol>,
+    strings: Vec<&'static str>,
+}
+
+impl Interner {
+    // Creates an interner with the keywords already interned.
+    fn new() -> Interner {
+        let mut interner = Interner {
+            symbols: HashMap::new(),
+            strings: Vec::new(),
+        };
+        for keyword in KEYWORDS {
+            interner.intern(keyword);
+        }
+        interner
+    }
+
+
+    // Interns `text`, returning its symbol.
+    fn intern(&mut self, text: &str) -> Symbol {
+        if let Some(&sym) = self.symbols.get(text) {
+            return sym;
+        }
+        let sym = Symbol(self.strings.len() as u32);
+        let text: &'static str = Box::leak(text.to_string().into_boxed_str());
+        self.strings.push(text);
+        self.symbols.insert(text, sym);
+        sym
+    }
+
+
+    // Returns the text of a symbol from this interner.
+    fn get(&self, sym: Symbol) -> &'static str {
+        self.strings[sym.0 as usize]
+    }
+}
+
+impl Default for Interner {
+    fn default() -> Interner {
+        Interner::new()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intern() {
+        let a = Symbol::intern("hello");
+        let b = Symbol::intern("there");
+        assert_ne!(a, b);
+        assert_eq!(a, Symbol::intern("hello"));
+        assert_eq!(a.as_str(), "hello");
+        assert_eq!(b.as_str(), "there");
+        assert!(!a.is_keyword());
+    }
+
+    #[test]
+    fn keywords() {
+        for (i, keyword) in KEYWORDS.iter().enumerate() {
+            let sym = Symbol::intern(keyword);
+            assert_eq!(sym.as_u32(), i as u32);
+            assert!(sym.is_keyword());
+        }
+        assert_eq!(kw::NAMESPACE.as_str(), "namespace");
+        assert_eq!(kw::FN.as_str(), "fn");
+        assert_eq!(kw::RETURN.as_str(), "return");
+        assert_eq!(kw::TYPE.as_str(), "type");
+    }
+
+    #[test]
+    fn separate_interner() {
+        let mut interner = Interner::new();
+        let a = interner.intern("a");
+        assert_eq!(a.as_u32() as usize, KEYWORDS.len());
+        assert_eq!(interner.get(a), "a");
+        assert_eq!(interner.intern("fn"), kw::FN);
+    }
+}
@@ -2,166 +2,352 @@
 #![allow(non_camel_case_types)]
 
 use std::fmt::{Display, Formatter, Error};
-use source_span::SourceSpan;
+use source_map::Span;
+use symbol::{Symbol, kw};
 
-#[derive(Eq, PartialEq, Copy, Clone, Debug)]
-pub enum Token<'a> {
+/// The kinds of tokens.
+#[derive(Eq, PartialEq, Copy, Clone, Hash, Debug)]
+pub enum TokenKind {
     // Catch-all
-    Unknown(SourceSpan<'a>),
-    
+    Unknown,
+
     // Catch-all for valid but as-of-yet unused symbols
-    Reserved(SourceSpan<'a>),
+    Reserved,
 
     // User-defined symbols
-    Identifier(SourceSpan<'a>),
-    IdentifierGeneric(SourceSpan<'a>),
-    Operator(SourceSpan<'a>),
+    Identifier,
+    IdentifierGeneric,
+    Operator,
 
     // Literals
-    LIT_Int(SourceSpan<'a>),
-    LIT_Real(SourceSpan<'a>),
-    LIT_String(SourceSpan<'a>),
-    LIT_RawString(SourceSpan<'a>),
+    LIT_Int,
+    LIT_Real,
+    LIT_String,
+    LIT_RawString,
+    LIT_Codepoint,
 
     // Documentation string
-    DocComment(SourceSpan<'a>),
+    DocComment,
+
+    // Compiler directive name, e.g. "%import"
+    Directive,
 
     // Punctuation
-    NewLine(SourceSpan<'a>),
-    LParen(SourceSpan<'a>),
-    RParen(SourceSpan<'a>),
-    LSquare(SourceSpan<'a>),
-    RSquare(SourceSpan<'a>),
-    LCurly(SourceSpan<'a>),
-    RCurly(SourceSpan<'a>),
-    Comma(SourceSpan<'a>),
-    Colon(SourceSpan<'a>),
-    At(SourceSpan<'a>),
-    Period(SourceSpan<'a>),
-    BackTick(SourceSpan<'a>),
-    Dollar(SourceSpan<'a>),
+    NewLine,
+    LParen,
+    RParen,
+    LSquare,
+    RSquare,
+    LCurly,
+    RCurly,
+    Comma,
+    Colon,
+    At,
+    Period,
+    BackTick,
+    Dollar,
 
     // Keywords
-    KEY_Namespace(SourceSpan<'a>),
-    KEY_Pub(SourceSpan<'a>),
-    KEY_Unsafe(SourceSpan<'a>),
+    KEY_Namespace,
+    KEY_Pub,
+    KEY_Unsafe,
 
-    KEY_Const(SourceSpan<'a>),
-    KEY_Val(SourceSpan<'a>),
-    KEY_Var(SourceSpan<'a>),
+    KEY_Const,
+    KEY_Val,
+    KEY_Var,
 
-    KEY_Mut(SourceSpan<'a>),
-    KEY_Ref(SourceSpan<'a>),
+    KEY_Mut,
+    KEY_Ref,
 
-    KEY_Fn(SourceSpan<'a>),
+    KEY_Fn,
 
-    KEY_Struct(SourceSpan<'a>),
-    KEY_Enum(SourceSpan<'a>),
-    KEY_Union(SourceSpan<'a>),
+    KEY_Struct,
+    KEY_Enum,
+    KEY_Union,
 
-    KEY_Trait(SourceSpan<'a>),
-    KEY_Is(SourceSpan<'a>),
+    KEY_Trait,
+    KEY_Is,
 
-    KEY_If(SourceSpan<'a>),
-    KEY_Else(SourceSpan<'a>),
-    KEY_Loop(SourceSpan<'a>),
-    KEY_While(SourceSpan<'a>),
-    KEY_Until(SourceSpan<'a>),
-    KEY_For(SourceSpan<'a>),
-    KEY_In(SourceSpan<'a>),
-    KEY_Break(SourceSpan<'a>),
-    KEY_Continue(SourceSpan<'a>),
-    KEY_Return(SourceSpan<'a>),
+    KEY_If,
+    KEY_Else,
+    KEY_Loop,
+    KEY_While,
+    KEY_Until,
+    KEY_For,
+    KEY_In,
+    KEY_Break,
+    KEY_Continue,
+    KEY_Return,
 
-    KEY_As(SourceSpan<'a>),
+    KEY_As,
 
-    KEY_Alias(SourceSpan<'a>),
-    KEY_Type(SourceSpan<'a>),
+    KEY_Alias,
+    KEY_Type,
 
     // EOF
     EOF,
 }
 
 
-impl<'a> Token<'a> {
-    pub fn source_span(&self) -> Option<SourceSpan<'a>> {
+impl TokenKind {
+    /// The name of the kind of token.  These names are stable, and can be
+    /// relied on by tools.
+    pub fn name(&self) -> &'static str {
         match *self {
-            // Catch-all
-            Token::Unknown(ss) => Some(ss),
-            
-            // Catch-all for valid but as-of-yet unused symbols
-            Token::Reserved(ss) => Some(ss),
-
-            // User-defined symbols
-            Token::Identifier(ss) => Some(ss),
-            Token::IdentifierGeneric(ss) => Some(ss),
-            Token::Operator(ss) => Some(ss),
-
-            // Literals
-            Token::LIT_Int(ss) => Some(ss),
-            Token::LIT_Real(ss) => Some(ss),
-            Token::LIT_String(ss) => Some(ss),
-            Token::LIT_RawString(ss) => Some(ss),
-
-            // Documentation string
-            Token::DocComment(ss) => Some(ss),
-
-            // Punctuation
-            Token::NewLine(ss) => Some(ss),
-            Token::LParen(ss) => Some(ss),
-            Token::RParen(ss) => Some(ss),
-            Token::LSquare(ss) => Some(ss),
-            Token::RSquare(ss) => Some(ss),
-            Token::LCurly(ss) => Some(ss),
-            Token::RCurly(ss) => Some(ss),
-            Token::Comma(ss) => Some(ss),
-            Token::Colon(ss) => Some(ss),
-            Token::At(ss) => Some(ss),
-            Token::Period(ss) => Some(ss),
-            Token::BackTick(ss) => Some(ss),
-            Token::Dollar(ss) => Some(ss),
-
-            // Keywords
-            Token::KEY_Namespace(ss) => Some(ss),
-            Token::KEY_Pub(ss) => Some(ss),
-            Token::KEY_Unsafe(ss) => Some(ss),
-
-            Token::KEY_Const(ss) => Some(ss),
-            Token::KEY_Val(ss) => Some(ss),
-            Token::KEY_Var(ss) => Some(ss),
-
-            Token::KEY_Mut(ss) => Some(ss),
-            Token::KEY_Ref(ss) => Some(ss),
-
-            Token::KEY_Fn(ss) => Some(ss),
-
-            Token::KEY_Struct(ss) => Some(ss),
-            Token::KEY_Enum(ss) => Some(ss),
-            Token::KEY_Union(ss) => Some(ss),
-
-            Token::KEY_Trait(ss) => Some(ss),
-            Token::KEY_Is(ss) => Some(ss),
-
-            Token::KEY_If(ss) => Some(ss),
-            Token::KEY_Else(ss) => Some(ss),
-            Token::KEY_Loop(ss) => Some(ss),
-            Token::KEY_While(ss) => Some(ss),
-            Token::KEY_Until(ss) => Some(ss),
-            Token::KEY_For(ss) => Some(ss),
-            Token::KEY_In(ss) => Some(ss),
-            Token::KEY_Break(ss) => Some(ss),
-            Token::KEY_Continue(ss) => Some(ss),
-            Token::KEY_Return(ss) => Some(ss),
-
-            Token::KEY_As(ss) => Some(ss),
-
-            Token::KEY_Alias(ss) => Some(ss),
-            Token::KEY_Type(ss) => Some(ss),
-
-            // EOF
-            Token::EOF => None,
+            TokenKind::Unknown => "Unknown",
+            TokenKind::Reserved => "Reserved",
+
+            TokenKind::Identifier => "Identifier",
+            TokenKind::IdentifierGeneric => "IdentifierGeneric",
+            TokenKind::Operator => "Operator",
+
+            TokenKind::LIT_Int => "LIT_Int",
+            TokenKind::LIT_Real => "LIT_Real",
+            TokenKind::LIT_String => "LIT_String",
+            TokenKind::LIT_RawString => "LIT_RawString",
+            TokenKind::LIT_Codepoint => "LIT_Codepoint",
+
+            TokenKind::DocComment => "DocComment",
+
+            TokenKind::Directive => "Directive",
+
+            TokenKind::NewLine => "NewLine",
+            TokenKind::LParen => "LParen",
+            TokenKind::RParen => "RParen",
+            TokenKind::LSquare => "LSquare",
+            TokenKind::RSquare => "RSquare",
+            TokenKind::LCurly => "LCurly",
+            TokenKind::RCurly => "RCurly",
+            TokenKind::Comma => "Comma",
+            TokenKind::Colon => "Colon",
+            TokenKind::At => "At",
+            TokenKind::Period => "Period",
+            TokenKind::BackTick => "BackTick",
+            TokenKind::Dollar => "Dollar",
+
+            TokenKind::KEY_Namespace => "KEY_Namespace",
+            TokenKind::KEY_Pub => "KEY_Pub",
+            TokenKind::KEY_Unsafe => "KEY_Unsafe",
+            TokenKind::KEY_Const => "KEY_Const",
+            TokenKind::KEY_Val => "KEY_Val",
+            TokenKind::KEY_Var => "KEY_Var",
+            TokenKind::KEY_Mut => "KEY_Mut",
+            TokenKind::KEY_Ref => "KEY_Ref",
+            TokenKind::KEY_Fn => "KEY_Fn",
+            TokenKind::KEY_Struct => "KEY_Struct",
+            TokenKind::KEY_Enum => "KEY_Enum",
+            TokenKind::KEY_Union => "KEY_Union",
+            TokenKind::KEY_Trait => "KEY_Trait",
+            TokenKind::KEY_Is => "KEY_Is",
+            TokenKind::KEY_If => "KEY_If",
+            TokenKind::KEY_Else => "KEY_Else",
+            TokenKind::KEY_Loop => "KEY_Loop",
+            TokenKind::KEY_While => "KEY_While",
+            TokenKind::KEY_Until => "KEY_Until",
+            TokenKind::KEY_For => "KEY_For",
+            TokenKind::KEY_In => "KEY_In",
+            TokenKind::KEY_Break => "KEY_Break",
+            TokenKind::KEY_Continue => "KEY_Continue",
+            TokenKind::KEY_Return => "KEY_Return",
+            TokenKind::KEY_As => "KEY_As",
+            TokenKind::KEY_Alias => "KEY_Alias",
+            TokenKind::KEY_Type => "KEY_Type",
+
+            TokenKind::EOF => "EOF",
+        }
+    }
+
+
+    /// The source text of the kind of token, for kinds that always have
+    /// the same text.  For newlines this is "\n".
+    pub fn canonical_text(&self) -> Option<&'static str> {
+        if let Some(sym) = self.keyword_symbol() {
+            return Some(sym.as_str());
+        }
+        match *self {
+            TokenKind::NewLine => Some("\n"),
+            TokenKind::LParen => Some("("),
+            TokenKind::RParen => Some(")"),
+            TokenKind::LSquare => Some("["),
+            TokenKind::RSquare => Some("]"),
+            TokenKind::LCurly => Some("{"),
+            TokenKind::RCurly => Some("}"),
+            TokenKind::Comma => Some(","),
+            TokenKind::Colon => Some(":"),
+            TokenKind::At => Some("@"),
+            TokenKind::Period => Some("."),
+            TokenKind::BackTick => Some("`"),
+            TokenKind::Dollar => Some("$"),
+            TokenKind::EOF => Some(""),
+            _ => None,
+        }
+    }
+
+
+    /// Returns the kind of keyword token for a symbol, if the symbol is a
+    /// keyword.
+    pub fn from_keyword(sym: Symbol) -> Option<TokenKind> {
+        Some(match sym {
+            kw::NAMESPACE => TokenKind::KEY_Namespace,
+            kw::PUB => TokenKind::KEY_Pub,
+            kw::UNSAFE => TokenKind::KEY_Unsafe,
+            kw::CONST => TokenKind::KEY_Const,
+            kw::VAL => TokenKind::KEY_Val,
+            kw::VAR => TokenKind::KEY_Var,
+            kw::MUT => TokenKind::KEY_Mut,
+            kw::REF => TokenKind::KEY_Ref,
+            kw::FN => TokenKind::KEY_Fn,
+            kw::STRUCT => TokenKind::KEY_Struct,
+            kw::ENUM => TokenKind::KEY_Enum,
+            kw::UNION => TokenKind::KEY_Union,
+            kw::TRAIT => TokenKind::KEY_Trait,
+            kw::IS => TokenKind::KEY_Is,
+            kw::IF => TokenKind::KEY_If,
+            kw::ELSE => TokenKind::KEY_Else,
+            kw::LOOP => TokenKind::KEY_Loop,
+            kw::WHILE => TokenKind::KEY_While,
+            kw::UNTIL => TokenKind::KEY_Until,
+            kw::FOR => TokenKind::KEY_For,
+            kw::IN => TokenKind::KEY_In,
+            kw::BREAK => TokenKind::KEY_Break,
+            kw::CONTINUE => TokenKind::KEY_Continue,
+            kw::RETURN => TokenKind::KEY_Return,
+            kw::AS => TokenKind::KEY_As,
+            kw::ALIAS => TokenKind::KEY_Alias,
+            kw::TYPE => TokenKind::KEY_Type,
+            _ => return None,
+        })
+    }
+
+
+    /// The symbol of a keyword kind of token.
+    pub fn keyword_symbol(&self) -> Option<Symbol> {
+        Some(match *self {
+            TokenKind::KEY_Namespace => kw::NAMESPACE,
+            TokenKind::KEY_Pub => kw::PUB,
+            TokenKind::KEY_Unsafe => kw::UNSAFE,
+            TokenKind::KEY_Const => kw::CONST,
+            TokenKind::KEY_Val => kw::VAL,
+            TokenKind::KEY_Var => kw::VAR,
+            TokenKind::KEY_Mut => kw::MUT,
+            TokenKind::KEY_Ref => kw::REF,
+            TokenKind::KEY_Fn => kw::FN,
+            TokenKind::KEY_Struct => kw::STRUCT,
+            TokenKind::KEY_Enum => kw::ENUM,
+            TokenKind::KEY_Union => kw::UNION,
+            TokenKind::KEY_Trait => kw::TRAIT,
+            TokenKind::KEY_Is => kw::IS,
+            TokenKind::KEY_If => kw::IF,
+            TokenKind::KEY_Else => kw::ELSE,
+            TokenKind::KEY_Loop => kw::LOOP,
+            TokenKind::KEY_While => kw::WHILE,
+            TokenKind::KEY_Until => kw::UNTIL,
+            TokenKind::KEY_For => kw::FOR,
+            TokenKind::KEY_In => kw::IN,
+            TokenKind::KEY_Break => kw::BREAK,
+            TokenKind::KEY_Continue => kw::CONTINUE,
+            TokenKind::KEY_Return => kw::RETURN,
+            TokenKind::KEY_As => kw::AS,
+            TokenKind::KEY_Alias => kw::ALIAS,
+            TokenKind::KEY_Type => kw::TYPE,
+            _ => return None,
+        })
+    }
+
+
+    pub fn is_keyword(&self) -> bool {
+        self.keyword_symbol().is_some()
+    }
+
+
+    pub fn is_literal(&self) -> bool {
+        matches!(*self,
+            TokenKind::LIT_Int |
+            TokenKind::LIT_Real |
+            TokenKind::LIT_String |
+            TokenKind::LIT_RawString |
+            TokenKind::LIT_Codepoint
+        )
+    }
+
+
+    pub fn is_punctuation(&self) -> bool {
+        matches!(*self,
+            TokenKind::NewLine |
+            TokenKind::LParen |
+            TokenKind::RParen |
+            TokenKind::LSquare |
+            TokenKind::RSquare |
+            TokenKind::LCurly |
+            TokenKind::RCurly |
+            TokenKind::Comma |
+            TokenKind::Colon |
+            TokenKind::At |
+            TokenKind::Period |
+            TokenKind::BackTick |
+            TokenKind::Dollar
+        )
+    }
+}
+
+
+impl Display for TokenKind {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.write_str(self.name())
+    }
+}
+
+
+//=====================================
+/// A token: its kind, the source text it was lexed from, and the span of
+/// that text.
+///
+/// EOF tokens have an empty span at the end of the source text.
+/// Identifiers also carry the symbol of their (normalized) name.
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub span: Span,
+    symbol: Option<Symbol>,
+}
+
+
+impl<'a> Token<'a> {
+    pub fn new(kind: TokenKind, text: &'a str, span: Span) -> Token<'a> {
+        Token {
+            kind: kind,
+            text: text,
+            span: span,
+            symbol: None,
         }
     }
+
+
+    /// Creates an identifier token with the symbol of its name.
+    pub fn new_identifier(kind: TokenKind, text: &'a str, span: Span, symbol: Symbol) -> Token<'a> {
+        Token {
+            kind: kind,
+            text: text,
+            span: span,
+            symbol: Some(symbol),
+        }
+    }
+
+
+    /// Returns the symbol of an identifier or keyword token.
+    pub fn symbol(&self) -> Option<Symbol> {
+        match self.symbol {
+            Some(sym) => Some(sym),
+            None => self.kind.keyword_symbol(),
+        }
+    }
+
+
+    /// The source text of the token.
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
 }
 
 
@@ -169,15 +355,92 @@ impl<'a> Token<'a> {
 // Prettier printing of tokens
 impl<'a> Display for Token<'a> {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
-        let debug_string = format!("{:?}", *self);
-        let type_name = &debug_string[..].split("(").next().unwrap();
-        
-        if let Some(ss) = self.source_span() {
-            f.write_str(&format!("{}: [{}:{}]  \t{}", type_name, ss.line, ss.column, ss.span)[..])
+        if self.kind == TokenKind::EOF {
+            f.write_str(self.kind.name())
         }
         else {
-            f.write_str(&format!("{}", type_name)[..])    
+            f.write_str(&format!("{}: [{}..{}]  \t{}", self.kind.name(), self.span.lo, self.span.hi, self.text)[..])
         }
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lexer::lex_str;
+
+    // All of the kinds of tokens
+    const KINDS: &'static [TokenKind] = &[
+        TokenKind::Unknown, TokenKind::Reserved,
+        TokenKind::Identifier, TokenKind::IdentifierGeneric, TokenKind::Operator,
+        TokenKind::LIT_Int, TokenKind::LIT_Real, TokenKind::LIT_String, TokenKind::LIT_RawString, TokenKind::LIT_Codepoint,
+        TokenKind::DocComment, TokenKind::Directive,
+        TokenKind::NewLine, TokenKind::LParen, TokenKind::RParen, TokenKind::LSquare, TokenKind::RSquare,
+        TokenKind::LCurly, TokenKind::RCurly, TokenKind::Comma, TokenKind::Colon, TokenKind::At,
+        TokenKind::Period, TokenKind::BackTick, TokenKind::Dollar,
+        TokenKind::KEY_Namespace, TokenKind::KEY_Pub, TokenKind::KEY_Unsafe,
+        TokenKind::KEY_Const, TokenKind::KEY_Val, TokenKind::KEY_Var,
+        TokenKind::KEY_Mut, TokenKind::KEY_Ref, TokenKind::KEY_Fn,
+        TokenKind::KEY_Struct, TokenKind::KEY_Enum, TokenKind::KEY_Union,
+        TokenKind::KEY_Trait, TokenKind::KEY_Is,
+        TokenKind::KEY_If, TokenKind::KEY_Else, TokenKind::KEY_Loop, TokenKind::KEY_While,
+        TokenKind::KEY_Until, TokenKind::KEY_For, TokenKind::KEY_In, TokenKind::KEY_Break,
+        TokenKind::KEY_Continue, TokenKind::KEY_Return, TokenKind::KEY_As,
+        TokenKind::KEY_Alias, TokenKind::KEY_Type,
+        TokenKind::EOF,
+    ];
+
+    #[test]
+    fn names() {
+        assert_eq!(TokenKind::LIT_Int.name(), "LIT_Int");
+        assert_eq!(TokenKind::KEY_Fn.name(), "KEY_Fn");
+        assert_eq!(format!("{}", TokenKind::NewLine), "NewLine");
+        for kind in KINDS {
+            assert_eq!(kind.name(), format!("{:?}", kind));
+        }
+    }
+
+    #[test]
+    fn categories() {
+        assert!(TokenKind::KEY_Return.is_keyword());
+        assert!(!TokenKind::Identifier.is_keyword());
+        assert!(TokenKind::LIT_Codepoint.is_literal());
+        assert!(!TokenKind::DocComment.is_literal());
+        assert!(TokenKind::Comma.is_punctuation());
+        assert!(!TokenKind::Operator.is_punctuation());
+        for kind in KINDS {
+            let categories = [kind.is_keyword(), kind.is_literal(), kind.is_punctuation()];
+            assert!(categories.iter().filter(|&&c| c).count() <= 1, "{}", kind);
+        }
+    }
+
+    #[test]
+    fn canonical_text_round_trip() {
+        // Every kind with canonical text lexes back to the same kind.
+        for kind in KINDS {
+            if let Some(text) = kind.canonical_text() {
+                let (tokens, _) = lex_str(text);
+                assert_eq!(tokens[0].kind, *kind, "{:?}", text);
+            }
+        }
+        assert_eq!(TokenKind::KEY_Namespace.canonical_text(), Some("namespace"));
+        assert_eq!(TokenKind::Identifier.canonical_text(), None);
+    }
+
+    #[test]
+    fn keyword_symbols() {
+        for kind in KINDS {
+            if let Some(sym) = kind.keyword_symbol() {
+                assert_eq!(TokenKind::from_keyword(sym), Some(*kind));
+            }
+        }
+    }
+
+    #[test]
+    fn display() {
+        let (tokens, _) = lex_str("foo");
+        assert_eq!(format!("{}", tokens[0]), "Identifier: [0..3]  \tfoo");
+        assert_eq!(format!("{}", tokens[1]), "EOF");
+    }
+}
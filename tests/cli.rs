@@ -0,0 +1,168 @@
+//! Tests of the `rune` command line tool.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+// Runs rune with the given arguments and stdin.
+fn rune(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rune"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+// Writes a temporary file for a test, returning its path.
+fn temp_file(name: &str, text: &str) -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    path.push(name);
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+#[test]
+fn usage_errors() {
+    assert_eq!(rune(&[], "").status.code(), Some(2));
+    assert_eq!(rune(&["lex"], "").status.code(), Some(2));
+    assert_eq!(rune(&["frobnicate", "-"], "").status.code(), Some(2));
+    assert_eq!(rune(&["check", "--error-format=xml", "-"], "").status.code(), Some(2));
+    assert_eq!(rune(&["--explain", "R9999"], "").status.code(), Some(2));
+
+    // Tokens and parse trees would be mixed up with machine-readable errors
+    assert_eq!(rune(&["lex", "--error-format=json", "-"], "").status.code(), Some(2));
+    assert_eq!(rune(&["parse", "--dump-ast", "--error-format=sarif", "-"], "").status.code(), Some(2));
+    assert_eq!(rune(&["parse", "--error-format=json", "-"], "").status.code(), Some(0));
+}
+
+#[test]
+fn help_and_version() {
+    let output = rune(&["--help"], "");
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).contains("rune check"));
+    assert_eq!(rune(&["--version"], "").status.code(), Some(0));
+}
+
+#[test]
+fn explain() {
+    let output = rune(&["--explain", "R0001"], "");
+    assert_eq!(output.status.code(), Some(0));
+    assert!(stdout(&output).starts_with("R0001: Unterminated string literal\n"));
+}
+
+#[test]
+fn lex_stdin() {
+    let output = rune(&["lex", "-"], "val a");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "KEY_Val: [1:1]  \tval\nIdentifier: [1:5]  \ta\nEOF\n");
+}
+
+#[test]
+fn parse_dump_ast() {
+    let output = rune(&["parse", "--dump-ast", "-"], "%import std.io\nval a = 1\n");
+    assert_eq!(output.status.code(), Some(0));
+    let dump = stdout(&output);
+    assert!(dump.starts_with("Directive: [1:1]  \t%import std.io\nRoot(\n"));
+    assert!(dump.contains("ValDecl {"));
+
+    let output = rune(&["parse", "-"], "val a\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("error[R0106]: \"val\" declarations need an initializer"));
+}
+
+#[test]
+fn check() {
+    let output = rune(&["check", "-"], "val a = 1\n");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "");
+    assert_eq!(stderr(&output), "");
+
+    let output = rune(&["check", "-"], "val a = 0x\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("error[R0005]: Missing digits after number base prefix.\n --> <stdin>:1:9\n"));
+}
+
+#[test]
+fn check_literals() {
+    let output = rune(&["check", "-"], "val small = 256u8\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("error[R0101]: Integer literal is too large for u8.\n --> <stdin>:1:13\n"));
+
+    let output = rune(&["run", "-"], "val a = '\\q'\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).starts_with("error[R0103]: "));
+    assert!(!stderr(&output).contains("Running programs isn't supported yet."));
+
+    // Literals that don't lex are only reported once
+    let output = rune(&["check", "-"], "val a = 0b2\n");
+    assert_eq!(stderr(&output).matches("error").count(), 1);
+
+    // `rune parse` only parses
+    assert_eq!(rune(&["parse", "-"], "val small = 256u8\n").status.code(), Some(0));
+}
+
+#[test]
+fn check_multiple_files() {
+    let good = temp_file("check_good.rune", "val a = 1\n");
+    let bad = temp_file("check_bad.rune", "val b = 0b2\n");
+    let output = rune(&["check", good.to_str().unwrap(), bad.to_str().unwrap(), "missing.rune"], "");
+    assert_eq!(output.status.code(), Some(1));
+    let errors = stderr(&output);
+    assert!(errors.contains("Couldn't read \"missing.rune\""));
+    assert!(errors.contains("check_bad.rune:1:"));
+    assert!(!errors.contains("check_good.rune"));
+}
+
+#[test]
+fn check_json() {
+    let output = rune(&["check", "--error-format=json", "-"], "val a = 0x\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).contains("\"code\": \"R0005\""));
+    assert_eq!(stderr(&output), "");
+}
+
+#[test]
+fn fmt() {
+    let output = rune(&["fmt", "-"], "val a = 1  \r\n\n\n");
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout(&output), "val a = 1\n");
+
+    let path = temp_file("fmt.rune", "val a = 1  \n");
+    let path = path.to_str().unwrap();
+    assert_eq!(rune(&["fmt", "--check", path], "").status.code(), Some(1));
+    assert_eq!(rune(&["fmt", path], "").status.code(), Some(0));
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "val a = 1\n");
+    assert_eq!(rune(&["fmt", "--check", path], "").status.code(), Some(0));
+
+    // Warnings, here for a confusable identifier, don't stop formatting
+    let path = temp_file("fmt_warning.rune", "val p\u{430}ypal = 1   \n");
+    let path = path.to_str().unwrap();
+    assert_eq!(rune(&["fmt", "--check", path], "").status.code(), Some(1));
+    assert_eq!(rune(&["fmt", path], "").status.code(), Some(0));
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "val p\u{430}ypal = 1\n");
+
+    // Errors do
+    let output = rune(&["fmt", "-"], "val s = \"abc\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Couldn't format \"<stdin>\" because of the errors in it."));
+}
+
+#[test]
+fn run_unsupported() {
+    let output = rune(&["run", "-"], "val a = 1\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr(&output).contains("Running programs isn't supported yet."));
+}
@@ -0,0 +1,113 @@
+//! Tests of the library's public API, used the way other tools use it.
+
+extern crate rune;
+extern crate rustc_serialize;
+
+use rustc_serialize::json::Json;
+use rune::diagnostics::{codes, render, ErrorFormat, Severity};
+use rune::lexer::{lex_file, lex_str_lossless};
+use rune::lexer::lossless::to_source;
+use rune::parser::{parse_directives, parse_file, Declaration, Directive, LitInteger, NodeMap, ParseTree, Parseable};
+use rune::source_map::SourceMap;
+use rune::symbol::Symbol;
+use rune::token::TokenKind;
+
+#[test]
+fn lex_files() {
+    let mut map = SourceMap::new();
+    let a = map.add_file("a.rune", "val a = 1".to_string());
+    let b = map.add_file("b.rune", "\nvar b".to_string());
+
+    let (tokens, errors) = lex_file(map.file(a));
+    assert!(errors.is_empty());
+    let kinds: Vec<_> = tokens.iter().map(|t| t.kind).collect();
+    assert_eq!(kinds, vec![TokenKind::KEY_Val, TokenKind::Identifier, TokenKind::Operator, TokenKind::LIT_Int, TokenKind::EOF]);
+    assert_eq!(tokens[1].symbol(), Some(Symbol::intern("a")));
+
+    // Spans are relative to their own file
+    let (tokens, _) = lex_file(map.file(b));
+    let var = &tokens[1];
+    assert_eq!(var.span.file_id, b);
+    assert_eq!(map.text(var.span), "var");
+    assert_eq!(map.file_name(var.span), "b.rune");
+    assert_eq!(map.line_col(var.span).line, 1);
+}
+
+#[test]
+fn lossless_round_trip() {
+    let text = "  val a = 1  # One\r\n\n#: Doc\nfn foo\n";
+    let (tokens, errors) = lex_str_lossless(text);
+    assert!(errors.is_empty());
+    assert_eq!(to_source(&tokens[..]), text);
+}
+
+#[test]
+fn parse() {
+    let mut map = SourceMap::new();
+    let id = map.add_file("main.rune", "%import std.io\nval a = 42u8".to_string());
+    let (tokens, _) = lex_file(map.file(id));
+    let tokens: Vec<_> = tokens.into_iter().collect();
+
+    let (directives, errors) = parse_directives(&tokens[..]);
+    assert!(errors.is_empty());
+    match directives[0] {
+        Directive::Import(import) => assert_eq!(import.path_parts(), vec![Symbol::intern("std"), Symbol::intern("io")]),
+        d => panic!("Expected an import, got {:?}", d),
+    }
+
+    let lit_start = tokens.iter().position(|t| t.kind == TokenKind::LIT_Int).unwrap();
+    let (lit, rest) = LitInteger::parse(&tokens[lit_start..]).unwrap();
+    assert_eq!(lit.value().unwrap(), 42);
+    assert_eq!(rest[0].kind, TokenKind::EOF);
+}
+
+#[test]
+fn owned_tree() {
+    // The tree outlives the source and tokens it was parsed from
+    let tree = {
+        let mut map = SourceMap::new();
+        let id = map.add_file("main.rune", "val a = 1\nvar b: i32".to_string());
+        let tokens: Vec<_> = lex_file(map.file(id)).0.into_iter().collect();
+        let (tree, errors) = parse_file(&tokens[..]);
+        assert!(errors.is_empty());
+        tree
+    };
+    let decls = match tree {
+        ParseTree::Root(ns) => ns.decls,
+        ParseTree::Empty => panic!("Expected declarations"),
+    };
+
+    // Later passes keep information about nodes in side tables
+    let mut mutable = NodeMap::new();
+    for decl in &decls {
+        mutable.insert(decl.id(), matches!(*decl, Declaration::Var(_)));
+    }
+    assert!(!mutable[&decls[0].id()]);
+    assert!(mutable[&decls[1].id()]);
+}
+
+#[test]
+fn diagnostics() {
+    let mut map = SourceMap::new();
+    let id = map.add_file("main.rune", "val a = 0x\nval b = 256u8".to_string());
+    let (tokens, lex_errors) = lex_file(map.file(id));
+    let tokens: Vec<_> = tokens.into_iter().collect();
+    let (lit, _) = LitInteger::parse(&tokens[8..]).unwrap();
+    let parse_error = lit.value().unwrap_err();
+
+    let diags = vec![lex_errors[0].to_diagnostic(), parse_error.to_diagnostic()];
+    assert_eq!(diags[0].severity, Severity::Error);
+    assert_eq!(diags[0].code, Some(codes::MISSING_DIGITS));
+    assert_eq!(diags[1].code, Some(codes::NUMBER_OUT_OF_RANGE));
+
+    let human = render(ErrorFormat::Human, &diags, &map, false);
+    assert!(human.starts_with("error[R0005]: Missing digits after number base prefix.\n --> main.rune:1:9\n"));
+
+    let json = Json::from_str(&render(ErrorFormat::Json, &diags, &map, false)).unwrap();
+    let spans = json["diagnostics"][1]["spans"].as_array().unwrap();
+    assert_eq!(json["diagnostics"][1]["code"], Json::String("R0101".to_string()));
+    assert_eq!(spans[0]["line_start"], Json::U64(2));
+    assert_eq!(spans[0]["column_start"], Json::U64(9));
+
+    assert!(codes::find("R0101").unwrap().explanation.contains("256u8"));
+}